@@ -0,0 +1,133 @@
+//! Criterion benchmarks for the core [`FibHeap`] operations.
+//!
+//! Run with `cargo bench`. The workloads use a cheap LCG so that keys are
+//! spread out without depending on an RNG crate.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use feap::FibHeap;
+
+/// Number of elements each workload operates on.
+const SIZES: [u32; 3] = [100, 1_000, 10_000];
+
+/// Generates `n` pseudo-random keys from a fixed seed.
+fn keys(n: u32) -> Vec<u32> {
+    let mut state = 0x2545_f491_u32;
+    (0..n).map(|_| {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        state >> 8
+    }).collect()
+}
+
+/// Builds a heap holding all of `keys`.
+fn filled(keys: &[u32]) -> FibHeap<u32> {
+    let mut feap = FibHeap::new();
+    for &k in keys {
+        feap.insert(k);
+    }
+    feap
+}
+
+fn insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("insert");
+    for n in SIZES {
+        let keys = keys(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &keys, |b, keys| {
+            b.iter(|| black_box(filled(keys)));
+        });
+    }
+    group.finish();
+}
+
+fn extract_min(c: &mut Criterion) {
+    let mut group = c.benchmark_group("extract_min");
+    for n in SIZES {
+        let keys = keys(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &keys, |b, keys| {
+            b.iter_batched_ref(|| filled(keys), |feap| {
+                while let Some(v) = feap.extract_min() {
+                    black_box(v);
+                }
+            }, BatchSize::SmallInput);
+        });
+    }
+    group.finish();
+}
+
+fn decrease_key(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decrease_key");
+    for n in SIZES {
+        // Every key is shifted up by `n` so it can be decreased back into
+        // place without colliding with another key.
+        let keys: Vec<u32> = (0..n).map(|k| k + n).collect();
+        group.bench_with_input(BenchmarkId::from_parameter(n), &keys, |b, keys| {
+            b.iter_batched_ref(|| filled(keys), |feap| {
+                for &k in keys {
+                    feap.decrease_key(&k, k - n);
+                }
+            }, BatchSize::SmallInput);
+        });
+    }
+    group.finish();
+}
+
+fn decrease_key_by_handle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decrease_key_by_handle");
+    for n in SIZES {
+        let keys: Vec<u32> = (0..n).map(|k| k + n).collect();
+        group.bench_with_input(BenchmarkId::from_parameter(n), &keys, |b, keys| {
+            b.iter_batched_ref(|| {
+                let mut feap = FibHeap::new();
+                let handles: Vec<_> = keys.iter().map(|&k| feap.insert_with_handle(k)).collect();
+                (feap, handles)
+            }, |(feap, handles)| {
+                for (&k, &h) in keys.iter().zip(handles.iter()) {
+                    feap.decrease_key_by_handle(h, k - n);
+                }
+            }, BatchSize::SmallInput);
+        });
+    }
+    group.finish();
+}
+
+fn meld(c: &mut Criterion) {
+    let mut group = c.benchmark_group("meld");
+    for n in SIZES {
+        // Two heaps of `n / 2` keys each, so the melded heap holds `n` keys.
+        let keys = keys(n);
+        let (left, right) = keys.split_at(keys.len() / 2);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &(left, right), |b, &(left, right)| {
+            b.iter_batched(|| (filled(left), filled(right)), |(mut feap, other)| {
+                feap.meld(other);
+                feap
+            }, BatchSize::SmallInput);
+        });
+    }
+    group.finish();
+}
+
+fn mixed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mixed");
+    for n in SIZES {
+        let keys = keys(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &keys, |b, keys| {
+            b.iter(|| {
+                let mut feap = FibHeap::new();
+                for (i, &k) in keys.iter().enumerate() {
+                    feap.insert(k);
+                    if i % 3 == 2 {
+                        black_box(feap.extract_min());
+                    }
+                }
+                while let Some(v) = feap.extract_min() {
+                    black_box(v);
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, insert, extract_min, decrease_key, decrease_key_by_handle, meld, mixed);
+criterion_main!(benches);
@@ -0,0 +1,85 @@
+//! Shared driver for the fuzz targets. A byte stream is decoded into a
+//! sequence of heap operations which are applied to a [`FibHeap`] and to a
+//! [`BTreeMap`] multiset acting as the reference implementation. After every
+//! operation both have to agree and the heap has to pass
+//! [`validate`](FibHeap::validate).
+
+use std::collections::BTreeMap;
+
+use feap::FibHeap;
+
+/// The reference implementation, mapping each value to its multiplicity.
+#[derive(Default)]
+struct Oracle(BTreeMap<i32, usize>);
+
+impl Oracle {
+    fn insert(&mut self, val: i32) {
+        *self.0.entry(val).or_default() += 1;
+    }
+
+    fn remove(&mut self, val: i32) {
+        let count = self.0.get_mut(&val).expect("value is in the oracle");
+        *count -= 1;
+        if *count == 0 {
+            self.0.remove(&val);
+        }
+    }
+
+    fn min(&self) -> Option<i32> {
+        self.0.keys().next().copied()
+    }
+
+    /// Returns the `idx % len`-th distinct value, if there is any.
+    fn nth(&self, idx: usize) -> Option<i32> {
+        if self.0.is_empty() {
+            return None;
+        }
+        self.0.keys().nth(idx % self.0.len()).copied()
+    }
+}
+
+/// Decodes `data` into operations and runs them. `key` maps the raw 16 bit
+/// value of an operation to the key that is actually used, which allows the
+/// targets to control how many duplicates show up.
+pub fn run(data: &[u8], key: fn(u16) -> i32) {
+    let mut feap = FibHeap::new();
+    let mut oracle = Oracle::default();
+
+    for op in data.chunks_exact(3) {
+        let arg = u16::from_le_bytes([op[1], op[2]]);
+        match op[0] % 8 {
+            0..=3 => {
+                let val = key(arg);
+                feap.insert(val);
+                oracle.insert(val);
+            }
+            4 | 5 => {
+                let min = oracle.min();
+                if let Some(min) = min {
+                    oracle.remove(min);
+                }
+                assert_eq!(feap.extract_min(), min);
+            }
+            6 => {
+                if let Some(old) = oracle.nth(arg as usize) {
+                    let new = old - i32::from(op[1] % 64);
+                    feap.decrease_key(&old, new);
+                    oracle.remove(old);
+                    oracle.insert(new);
+                }
+            }
+            _ => {
+                feap.clear();
+                oracle.0.clear();
+            }
+        }
+        feap.validate();
+        assert_eq!(feap.get_min().copied(), oracle.min());
+    }
+
+    while let Some(min) = oracle.min() {
+        oracle.remove(min);
+        assert_eq!(feap.extract_min(), Some(min));
+    }
+    assert_eq!(feap.extract_min(), None);
+}
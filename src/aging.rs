@@ -0,0 +1,113 @@
+//! A priority queue in which waiting items gain priority over time, so items
+//! with a low priority are not starved by a stream of more urgent ones.
+
+use crate::{FibHeap, Keyed};
+
+/// A queue of items whose priority improves linearly with the time they wait.
+/// Smaller priorities come first, and every time unit an item waits lowers
+/// its priority by `rate`.
+///
+/// Since all items age at the same rate, their order never changes while
+/// they wait. Each item is therefore keyed once by its priority at time zero,
+/// `priority + rate * enqueued`, and nothing has to be re-keyed on a tick.
+/// The effective priority at time `now` is that key minus `rate * now`.
+///
+/// ```rust
+/// use feap::AgingQueue;
+///
+/// let mut queue = AgingQueue::new(1.0);
+/// queue.push("batch job", 50.0, 0);
+/// queue.push("request", 10.0, 45);
+/// queue.push("request", 10.0, 60);
+///
+/// // After waiting 60 ticks the batch job is at -10 and overtakes both.
+/// assert_eq!(queue.pop(60), Some((-10.0, "batch job")));
+/// assert_eq!(queue.pop(60), Some((-5.0, "request")));
+/// ```
+pub struct AgingQueue<T> {
+    /// The items, keyed by their priority at time zero.
+    heap: FibHeap<Keyed<f64, T>>,
+
+    /// The priority gained per time unit of waiting.
+    rate: f64,
+}
+
+impl<T> AgingQueue<T> {
+    /// Create a new, empty [`AgingQueue`] in which items gain `rate` priority
+    /// per time unit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is negative or not finite.
+    pub fn new(rate: f64) -> Self {
+        assert!(rate >= 0.0 && rate.is_finite(), "the rate has to be finite and not negative");
+        Self {
+            heap: FibHeap::new(),
+            rate,
+        }
+    }
+
+    /// `push` adds `item` with `priority` at time `now`. The priority must
+    /// not be NaN.
+    pub fn push(&mut self, item: T, priority: f64, now: u64) {
+        self.heap.insert(Keyed::new(priority + self.rate * now as f64, item));
+    }
+
+    /// `peek` returns the item with the smallest effective priority at time
+    /// `now`, together with that priority.
+    pub fn peek(&self, now: u64) -> Option<(f64, &T)> {
+        self.heap.get_min().map(|k| (self.effective(k.key, now), &k.value))
+    }
+
+    /// `pop` removes the item with the smallest effective priority at time
+    /// `now` and returns it together with that priority.
+    pub fn pop(&mut self, now: u64) -> Option<(f64, T)> {
+        let Keyed { key, value } = self.heap.extract_min()?;
+        Some((self.effective(key, now), value))
+    }
+
+    /// Turns a key back into the priority at time `now`.
+    fn effective(&self, key: f64, now: u64) -> f64 {
+        key - self.rate * now as f64
+    }
+
+    /// `len` returns the number of queued items.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// `is_empty` returns whether no items are queued.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AgingQueue;
+
+    #[test]
+    fn matches_rekeying() {
+        let mut queue = AgingQueue::new(0.5);
+        let mut waiting = Vec::new();
+        let mut state = 3u32;
+        for now in 0..1000u64 {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let priority = f64::from(state >> 24);
+            queue.push(now, priority, now);
+            waiting.push((priority, now));
+
+            if now % 3 == 0 {
+                // Equal priorities may come out in any order, so only the
+                // priority is compared with a scan over the aged items.
+                let aged = |&(p, t): &(f64, u64)| p - 0.5 * (now - t) as f64;
+                let expected = waiting.iter().map(aged).min_by(f64::total_cmp).unwrap();
+                let (priority, item) = queue.pop(now).unwrap();
+                assert_eq!(priority, expected);
+                let idx = waiting.iter().position(|&(_, t)| t == item).unwrap();
+                assert_eq!(aged(&waiting.swap_remove(idx)), expected);
+            }
+        }
+        assert_eq!(queue.len(), waiting.len());
+    }
+}
@@ -0,0 +1,296 @@
+//! An earliest-deadline-first executor for futures. This module is only
+//! available with the `async` feature.
+
+use core::cmp::Ordering;
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{self, AtomicBool};
+use core::task::{Context, Poll, Waker};
+use std::sync::{Arc, Mutex};
+use std::task::Wake;
+
+use crate::FibHeap;
+
+/// Identifies a task spawned on an [`EdfExecutor`]. The id of a finished task
+/// may be reused by a later [`spawn`](EdfExecutor::spawn).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskId(usize);
+
+/// The waker of a task. It queues the task on the executor's wake list at
+/// most once until the task is polled again.
+struct TaskWaker {
+    id: usize,
+    queued: AtomicBool,
+    woken: Arc<Mutex<Vec<Arc<TaskWaker>>>>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.queued.swap(true, atomic::Ordering::AcqRel) {
+            self.woken.lock().unwrap().push(self.clone());
+        }
+    }
+}
+
+/// A spawned future together with its deadline.
+struct Task<D> {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    deadline: D,
+    waker: Arc<TaskWaker>,
+}
+
+/// An entry of the ready heap, ordered by deadline and, for equal deadlines,
+/// by the order in which the tasks became ready.
+struct Ready<D> {
+    deadline: D,
+    seq: u64,
+    id: usize,
+}
+
+impl<D: PartialOrd> PartialEq for Ready<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.seq == other.seq && self.deadline == other.deadline
+    }
+}
+
+impl<D: PartialOrd> PartialOrd for Ready<D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.deadline.partial_cmp(&other.deadline)? {
+            Ordering::Equal => Some(self.seq.cmp(&other.seq)),
+            ord => Some(ord),
+        }
+    }
+}
+
+/// A single threaded executor which always polls the ready task with the
+/// earliest deadline first. Tasks which are not ready are not in the heap and
+/// cost nothing until they are woken.
+///
+/// ```rust
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+/// use feap::EdfExecutor;
+///
+/// let log = Rc::new(RefCell::new(Vec::new()));
+/// let mut executor = EdfExecutor::new();
+/// for deadline in [30, 10, 20] {
+///     let log = log.clone();
+///     executor.spawn(deadline, async move { log.borrow_mut().push(deadline) });
+/// }
+///
+/// executor.run_until_stalled();
+/// assert_eq!(*log.borrow(), [10, 20, 30]);
+/// assert!(executor.is_empty());
+/// ```
+pub struct EdfExecutor<D: PartialOrd> {
+    /// All unfinished tasks, indexed by their id. Finished slots are reused.
+    tasks: Vec<Option<Task<D>>>,
+
+    /// The ids of the free slots in [`tasks`](EdfExecutor::tasks).
+    free: Vec<usize>,
+
+    /// The tasks which are ready to be polled.
+    ready: FibHeap<Ready<D>>,
+
+    /// The wakers which were woken since the last poll.
+    woken: Arc<Mutex<Vec<Arc<TaskWaker>>>>,
+
+    /// The sequence number for the next ready entry.
+    next_seq: u64,
+}
+
+impl<D: PartialOrd + Clone> Default for EdfExecutor<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D: PartialOrd + Clone> EdfExecutor<D> {
+    /// Create a new [`EdfExecutor`] without any tasks.
+    pub fn new() -> Self {
+        Self {
+            tasks: Vec::new(),
+            free: Vec::new(),
+            ready: FibHeap::new(),
+            woken: Arc::new(Mutex::new(Vec::new())),
+            next_seq: 0,
+        }
+    }
+
+    /// `spawn` adds `future` with the given `deadline`. The task is ready
+    /// right away.
+    pub fn spawn(&mut self, deadline: D, future: impl Future<Output = ()> + 'static) -> TaskId {
+        let id = self.free.pop().unwrap_or(self.tasks.len());
+        let waker = Arc::new(TaskWaker {
+            id,
+            queued: AtomicBool::new(false),
+            woken: self.woken.clone(),
+        });
+        let task = Task {
+            future: Box::pin(future),
+            deadline,
+            waker: waker.clone(),
+        };
+        if id == self.tasks.len() {
+            self.tasks.push(Some(task));
+        } else {
+            self.tasks[id] = Some(task);
+        }
+        waker.wake();
+        TaskId(id)
+    }
+
+    /// Moves the tasks which were woken into the ready heap. Wakers of tasks
+    /// which finished in the meantime are ignored.
+    fn collect_woken(&mut self) {
+        let woken = core::mem::take(&mut *self.woken.lock().unwrap());
+        for waker in woken {
+            if let Some(Some(task)) = self.tasks.get(waker.id) {
+                if Arc::ptr_eq(&task.waker, &waker) {
+                    self.ready.insert(Ready {
+                        deadline: task.deadline.clone(),
+                        seq: self.next_seq,
+                        id: waker.id,
+                    });
+                    self.next_seq += 1;
+                }
+            }
+        }
+    }
+
+    /// `poll_next` polls the ready task with the earliest deadline once and
+    /// returns its id, or `None` if no task is ready.
+    pub fn poll_next(&mut self) -> Option<TaskId> {
+        self.collect_woken();
+        let id = self.ready.extract_min()?.id;
+        let task = self.tasks[id].as_mut().unwrap();
+
+        // Clear the flag before polling, so wakes during the poll are seen.
+        task.waker.queued.store(false, atomic::Ordering::Release);
+        let waker = Waker::from(task.waker.clone());
+        let mut cx = Context::from_waker(&waker);
+        if let Poll::Ready(()) = task.future.as_mut().poll(&mut cx) {
+            self.tasks[id] = None;
+            self.free.push(id);
+        }
+        Some(TaskId(id))
+    }
+
+    /// `run_until_stalled` polls ready tasks in deadline order until none is
+    /// ready anymore and returns the number of polls.
+    pub fn run_until_stalled(&mut self) -> usize {
+        let mut polls = 0;
+        while self.poll_next().is_some() {
+            polls += 1;
+        }
+        polls
+    }
+
+    /// `is_finished` returns whether the task `id` has completed.
+    pub fn is_finished(&self, id: TaskId) -> bool {
+        self.tasks.get(id.0).is_none_or(|t| t.is_none())
+    }
+
+    /// `len` returns the number of unfinished tasks.
+    pub fn len(&self) -> usize {
+        self.tasks.len() - self.free.len()
+    }
+
+    /// `is_empty` returns whether all tasks have finished.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll, Waker};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::EdfExecutor;
+
+    /// Returns `Pending` once, waking itself, and is ready on the next poll.
+    struct YieldNow(bool);
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    /// Pending until the shared flag is set, registering its waker.
+    struct Signal(Rc<RefCell<(bool, Option<Waker>)>>);
+
+    impl Future for Signal {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            let mut state = self.0.borrow_mut();
+            if state.0 {
+                Poll::Ready(())
+            } else {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn earliest_deadline_first() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut executor = EdfExecutor::new();
+        for deadline in [3, 1, 2] {
+            let log = log.clone();
+            executor.spawn(deadline, async move {
+                log.borrow_mut().push((deadline, 'a'));
+                YieldNow(false).await;
+                log.borrow_mut().push((deadline, 'b'));
+            });
+        }
+
+        assert_eq!(executor.run_until_stalled(), 6);
+        assert_eq!(*log.borrow(), [(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b'), (3, 'a'),
+            (3, 'b')]);
+    }
+
+    #[test]
+    fn woken_by_other_task() {
+        let signal = Rc::new(RefCell::new((false, None::<Waker>)));
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut executor = EdfExecutor::new();
+
+        let (s, l) = (signal.clone(), log.clone());
+        let waiter = executor.spawn(1, async move {
+            Signal(s).await;
+            l.borrow_mut().push("waiter");
+        });
+        assert_eq!(executor.run_until_stalled(), 1);
+        assert!(!executor.is_finished(waiter));
+
+        let (s, l) = (signal.clone(), log.clone());
+        executor.spawn(5, async move {
+            l.borrow_mut().push("signaller");
+            let mut state = s.borrow_mut();
+            state.0 = true;
+            state.1.take().unwrap().wake();
+        });
+        assert_eq!(executor.run_until_stalled(), 2);
+        assert_eq!(*log.borrow(), ["signaller", "waiter"]);
+        assert!(executor.is_finished(waiter));
+        assert!(executor.is_empty());
+    }
+}
@@ -0,0 +1,25 @@
+//! The errors of the fallible [`FibHeap`](crate::FibHeap) operations.
+
+use core::fmt;
+
+/// Why an operation on a [`FibHeap`](crate::FibHeap) was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeapError {
+    /// No value in the heap matched.
+    NotFound,
+
+    /// The new value of a decrease-key was greater than, or not comparable
+    /// to, the old one.
+    NewKeyGreater,
+}
+
+impl fmt::Display for FeapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => f.write_str("no matching value in the heap"),
+            Self::NewKeyGreater => f.write_str("the new key is greater than the old one"),
+        }
+    }
+}
+
+impl std::error::Error for FeapError {}
@@ -0,0 +1,148 @@
+//! An event queue on top of [`FibHeap`] for loops which process everything
+//! that is due each frame, like game loops and simulations.
+
+use core::cmp::Ordering;
+
+use crate::FibHeap;
+
+/// An event together with the time it is due and a sequence number which
+/// breaks ties between events due at the same time.
+struct Scheduled<T, E> {
+    time: T,
+    seq: u64,
+    event: E,
+}
+
+impl<T: PartialOrd, E> PartialEq for Scheduled<T, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.seq == other.seq && self.time == other.time
+    }
+}
+
+impl<T: PartialOrd, E> PartialOrd for Scheduled<T, E> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.time.partial_cmp(&other.time)? {
+            Ordering::Equal => Some(self.seq.cmp(&other.seq)),
+            ord => Some(ord),
+        }
+    }
+}
+
+/// A queue of events keyed by the time they are due. Events due at the same
+/// time are popped in the order they were scheduled, so processing them is
+/// deterministic.
+///
+/// ```rust
+/// use feap::EventQueue;
+///
+/// let mut events = EventQueue::new();
+/// events.schedule(2, "b");
+/// events.schedule(1, "a");
+/// events.schedule(2, "c");
+/// events.schedule(5, "d");
+///
+/// let due: Vec<_> = events.pop_until(2).collect();
+/// assert_eq!(due, [(1, "a"), (2, "b"), (2, "c")]);
+/// assert_eq!(events.peek_time(), Some(&5));
+/// ```
+pub struct EventQueue<T: PartialOrd, E> {
+    /// The scheduled events ordered by time and sequence number.
+    heap: FibHeap<Scheduled<T, E>>,
+
+    /// The sequence number the next scheduled event gets.
+    next_seq: u64,
+}
+
+impl<T: PartialOrd, E> Default for EventQueue<T, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd, E> EventQueue<T, E> {
+    /// Create a new, empty [`EventQueue`].
+    pub fn new() -> Self {
+        Self {
+            heap: FibHeap::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// `schedule` queues `event` to be due at `time`. It is popped after all
+    /// events which are due earlier or were scheduled earlier for the same
+    /// time.
+    pub fn schedule(&mut self, time: T, event: E) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.heap.insert(Scheduled { time, seq, event });
+    }
+
+    /// `peek_time` returns the time the next event is due, if there is any.
+    pub fn peek_time(&self) -> Option<&T> {
+        self.heap.get_min().map(|s| &s.time)
+    }
+
+    /// `pop` removes and returns the next event together with its time.
+    pub fn pop(&mut self) -> Option<(T, E)> {
+        self.heap.extract_min().map(|s| (s.time, s.event))
+    }
+
+    /// `pop_until` returns an iterator which pops all events due at or before
+    /// `now` in order. Events which are not consumed from the iterator stay in
+    /// the queue.
+    pub fn pop_until(&mut self, now: T) -> PopUntil<'_, T, E> {
+        PopUntil { queue: self, now }
+    }
+
+    /// `clear` removes all events.
+    pub fn clear(&mut self) {
+        self.heap.clear();
+    }
+}
+
+/// The iterator returned by [`EventQueue::pop_until`].
+pub struct PopUntil<'a, T: PartialOrd, E> {
+    queue: &'a mut EventQueue<T, E>,
+    now: T,
+}
+
+impl<T: PartialOrd, E> Iterator for PopUntil<'_, T, E> {
+    type Item = (T, E);
+
+    fn next(&mut self) -> Option<(T, E)> {
+        match self.queue.peek_time() {
+            Some(time) if *time <= self.now => self.queue.pop(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventQueue;
+
+    #[test]
+    fn same_time_is_fifo() {
+        let mut events = EventQueue::new();
+        for i in 0..300 {
+            events.schedule(i % 3, i);
+        }
+        for tick in 0..3 {
+            let due: Vec<_> = events.pop_until(tick).map(|(_, e)| e).collect();
+            let expected: Vec<_> = (0..300).filter(|i| i % 3 == tick).collect();
+            assert_eq!(due, expected);
+        }
+        assert_eq!(events.pop(), None);
+    }
+
+    #[test]
+    fn pop_until_stops() {
+        let mut events = EventQueue::new();
+        events.schedule(3, 'c');
+        events.schedule(1, 'a');
+        assert_eq!(events.pop_until(0).next(), None);
+
+        assert_eq!(events.pop_until(5).next(), Some((1, 'a')));
+        assert_eq!(events.peek_time(), Some(&3));
+    }
+}
@@ -0,0 +1,203 @@
+//! An index of keys with expiry times, as used for evicting entries from TTL
+//! caches.
+
+use core::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::FibHeap;
+
+/// The number of stale heap entries which is always tolerated before the heap
+/// is rebuilt.
+const COMPACTION_SLACK: usize = 32;
+
+/// A heap entry. It is stale if the key has been touched or removed since,
+/// which shows as a different generation in the map.
+struct Entry<K, T> {
+    expiry: T,
+    generation: u64,
+    key: K,
+}
+
+impl<K, T: PartialOrd> PartialEq for Entry<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.generation == other.generation && self.expiry == other.expiry
+    }
+}
+
+impl<K, T: PartialOrd> PartialOrd for Entry<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.expiry.partial_cmp(&other.expiry)? {
+            Ordering::Equal => Some(self.generation.cmp(&other.generation)),
+            ord => Some(ord),
+        }
+    }
+}
+
+/// Tracks keys together with the time they expire at. Expiry times can be
+/// moved in both directions with [`touch`](ExpiryIndex::touch), and
+/// [`evict_expired`](ExpiryIndex::evict_expired) removes every key which is due.
+///
+/// Moving an expiry time does not search the heap. The old heap entry is left
+/// behind and skipped once it comes up, and the heap is rebuilt when such
+/// stale entries start to outnumber the live ones.
+///
+/// ```rust
+/// use feap::ExpiryIndex;
+///
+/// let mut index = ExpiryIndex::new();
+/// index.touch("session-a", 10);
+/// index.touch("session-b", 20);
+/// index.touch("session-a", 30);
+///
+/// assert_eq!(index.evict_expired(&25), ["session-b"]);
+/// assert_eq!(index.expiry(&"session-a"), Some(&30));
+/// ```
+pub struct ExpiryIndex<K, T: PartialOrd> {
+    /// The current expiry time and generation of every key.
+    keys: HashMap<K, (T, u64)>,
+
+    /// Live and stale entries ordered by expiry time.
+    heap: FibHeap<Entry<K, T>>,
+
+    /// The generation the next touched key gets.
+    next_generation: u64,
+}
+
+impl<K: Hash + Eq + Clone, T: PartialOrd + Clone> Default for ExpiryIndex<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq + Clone, T: PartialOrd + Clone> ExpiryIndex<K, T> {
+    /// Create a new, empty [`ExpiryIndex`].
+    pub fn new() -> Self {
+        Self {
+            keys: HashMap::new(),
+            heap: FibHeap::new(),
+            next_generation: 0,
+        }
+    }
+
+    /// `touch` sets the expiry time of `key` to `expiry`, adding the key if it
+    /// is not tracked yet. The new time may be earlier or later than the old
+    /// one.
+    pub fn touch(&mut self, key: K, expiry: T) {
+        let generation = self.next_generation;
+        self.next_generation += 1;
+        self.keys.insert(key.clone(), (expiry.clone(), generation));
+        self.heap.insert(Entry { expiry, generation, key });
+        self.compact_if_stale();
+    }
+
+    /// `remove` stops tracking `key` and returns its expiry time.
+    pub fn remove(&mut self, key: &K) -> Option<T> {
+        let (expiry, _) = self.keys.remove(key)?;
+        self.compact_if_stale();
+        Some(expiry)
+    }
+
+    /// `expiry` returns the expiry time of `key`.
+    pub fn expiry(&self, key: &K) -> Option<&T> {
+        self.keys.get(key).map(|(expiry, _)| expiry)
+    }
+
+    /// `next_expiry` returns the earliest expiry time of all keys.
+    pub fn next_expiry(&mut self) -> Option<&T> {
+        self.drop_stale();
+        self.heap.get_min().map(|e| &e.expiry)
+    }
+
+    /// `evict_expired` removes all keys expiring at or before `now` and
+    /// returns them ordered by expiry time.
+    pub fn evict_expired(&mut self, now: &T) -> Vec<K> {
+        let mut evicted = Vec::new();
+        loop {
+            self.drop_stale();
+            match self.heap.get_min() {
+                Some(e) if e.expiry <= *now => {}
+                _ => break,
+            }
+            let entry = self.heap.extract_min().unwrap();
+            self.keys.remove(&entry.key);
+            evicted.push(entry.key);
+        }
+        evicted
+    }
+
+    /// `len` returns the number of tracked keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// `is_empty` returns whether no keys are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Whether `entry` still describes the current expiry of its key.
+    fn is_live(&self, entry: &Entry<K, T>) -> bool {
+        self.keys.get(&entry.key).is_some_and(|&(_, g)| g == entry.generation)
+    }
+
+    /// Pops stale entries until the minimum is a live one.
+    fn drop_stale(&mut self) {
+        while let Some(min) = self.heap.get_min() {
+            if self.is_live(min) {
+                break;
+            }
+            self.heap.extract_min();
+        }
+    }
+
+    /// Rebuilds the heap from the live keys once stale entries make up more
+    /// than half of it.
+    fn compact_if_stale(&mut self) {
+        if self.heap.len() <= 2 * self.keys.len() + COMPACTION_SLACK {
+            return;
+        }
+        self.heap.clear();
+        for (key, (expiry, generation)) in &self.keys {
+            self.heap.insert(Entry {
+                expiry: expiry.clone(),
+                generation: *generation,
+                key: key.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExpiryIndex;
+
+    #[test]
+    fn touch_moves_both_ways() {
+        let mut index = ExpiryIndex::new();
+        index.touch(1, 50);
+        index.touch(2, 60);
+        index.touch(3, 70);
+        index.touch(1, 80);
+        index.touch(3, 10);
+        assert_eq!(index.next_expiry(), Some(&10));
+        assert_eq!(index.evict_expired(&60), [3, 2]);
+        assert_eq!(index.remove(&1), Some(80));
+        assert_eq!(index.evict_expired(&100), Vec::<i32>::new());
+        assert!(index.is_empty());
+    }
+
+    #[test]
+    fn stale_entries_are_compacted() {
+        let mut index = ExpiryIndex::new();
+        for round in 0..100 {
+            for key in 0..10 {
+                index.touch(key, round * 10 + key);
+            }
+        }
+        assert_eq!(index.len(), 10);
+        assert!(index.heap.len() <= 2 * 10 + super::COMPACTION_SLACK);
+        assert_eq!(index.evict_expired(&994), [0, 1, 2, 3, 4]);
+        assert_eq!(index.next_expiry(), Some(&995));
+    }
+}
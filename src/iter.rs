@@ -0,0 +1,310 @@
+//! The iterators over the values of a [`FibHeap`].
+
+use core::iter::FusedIterator;
+use core::slice;
+
+use crate::{free_tree, take_node, take_unit, unit, FibHeap, Link};
+
+/// The iterator returned by [`FibHeap::iter`]. It walks the trees depth
+/// first, keeping the unvisited siblings of each level on a stack.
+pub struct Iter<'a, T> {
+    /// The remaining nodes of the root list and of each level below.
+    pub(crate) stack: Vec<slice::Iter<'a, Link<T>>>,
+
+    /// The number of [`units`](FibHeap::units) which are not yielded yet.
+    pub(crate) units: usize,
+
+    /// The number of values which are not yielded yet.
+    pub(crate) remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.units > 0 {
+            self.units -= 1;
+            self.remaining -= 1;
+            // The heap counts the unit for as long as it is borrowed.
+            return Some(unsafe { unit() });
+        }
+        loop {
+            let level = self.stack.last_mut()?;
+            match level.next() {
+                Some(&node) => {
+                    // The nodes are borrowed from the heap for `'a`, so they
+                    // are neither changed nor freed while the iterator lives.
+                    let node = unsafe { &*node };
+                    if !node.children.is_empty() {
+                        self.stack.push(node.children.iter());
+                    }
+                    self.remaining -= 1;
+                    return Some(&node.val);
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self { stack: self.stack.clone(), units: self.units, remaining: self.remaining }
+    }
+}
+
+/// The iterator returned by [`FibHeap::into_iter_sorted`].
+pub struct IntoIterSorted<T: PartialOrd> {
+    pub(crate) heap: FibHeap<T>,
+}
+
+impl<T: PartialOrd> Iterator for IntoIterSorted<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.heap.extract_min()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<T: PartialOrd> ExactSizeIterator for IntoIterSorted<T> {}
+
+impl<T: PartialOrd> FusedIterator for IntoIterSorted<T> {}
+
+/// The iterator returned by [`FibHeap::drain`]. The heap is already empty
+/// while it lives, the nodes which are not yielded yet are kept in its own
+/// list, which is the [`head_list`](FibHeap::head_list) taken from the heap.
+/// The list is handed back on drop, so its capacity is kept. If the iterator
+/// is leaked, the remaining values are leaked as well, but the heap stays
+/// valid.
+pub struct Drain<'a, T: PartialOrd> {
+    pub(crate) heap: &'a mut FibHeap<T>,
+
+    /// The roots of the trees which are not yielded yet.
+    pub(crate) pending: Vec<Link<T>>,
+
+    /// The number of [`units`](FibHeap::units) which are not yielded yet.
+    pub(crate) units: usize,
+
+    /// The number of values which are not yielded yet.
+    pub(crate) remaining: usize,
+}
+
+impl<T: PartialOrd> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.units > 0 {
+            self.units -= 1;
+            self.remaining -= 1;
+            return Some(unsafe { take_unit() });
+        }
+        let node = self.pending.pop()?;
+        self.remaining -= 1;
+        unsafe {
+            self.pending.extend_from_slice(&(*node).children);
+            Some(take_node(node))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: PartialOrd> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T: PartialOrd> FusedIterator for Drain<'_, T> {}
+
+impl<T: PartialOrd> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        while self.units > 0 {
+            self.units -= 1;
+            drop(unsafe { take_unit::<T>() });
+        }
+        for t in self.pending.drain(..) {
+            free_tree(t, self.heap.wipe);
+        }
+        self.heap.head_list = core::mem::take(&mut self.pending);
+    }
+}
+
+/// The iterator returned by [`FibHeap::drain_sorted`]. The values which are
+/// not yielded are removed from the heap on drop.
+pub struct DrainSorted<'a, T: PartialOrd> {
+    pub(crate) heap: &'a mut FibHeap<T>,
+}
+
+impl<T: PartialOrd> Iterator for DrainSorted<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.heap.extract_min()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<T: PartialOrd> ExactSizeIterator for DrainSorted<'_, T> {}
+
+impl<T: PartialOrd> FusedIterator for DrainSorted<'_, T> {}
+
+impl<T: PartialOrd> Drop for DrainSorted<'_, T> {
+    fn drop(&mut self) {
+        self.heap.clear();
+    }
+}
+
+/// The iterator returned by [`FibHeap::drain_while`]. The values which are
+/// not yielded stay in the heap.
+pub struct DrainWhile<'a, T: PartialOrd, F> {
+    pub(crate) heap: &'a mut FibHeap<T>,
+    pub(crate) pred: F,
+
+    /// Whether the predicate failed once, after which nothing is yielded.
+    pub(crate) done: bool,
+}
+
+impl<T: PartialOrd, F: FnMut(&T) -> bool> Iterator for DrainWhile<'_, T, F> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.done {
+            return None;
+        }
+        match self.heap.get_min() {
+            Some(min) if (self.pred)(min) => self.heap.extract_min(),
+            _ => {
+                self.done = true;
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let upper = if self.done { 0 } else { self.heap.len() };
+        (0, Some(upper))
+    }
+}
+
+impl<T: PartialOrd, F: FnMut(&T) -> bool> FusedIterator for DrainWhile<'_, T, F> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::FibHeap;
+
+    #[test]
+    fn iter_visits_every_value() {
+        let mut feap: FibHeap<_> = (0..300).rev().collect();
+        feap.extract_min();
+        feap.decrease_key(&200, -1);
+        feap.insert(1000);
+
+        let mut iter = feap.iter();
+        assert_eq!(iter.len(), 300);
+        iter.next();
+        assert_eq!(iter.len(), 299);
+
+        let mut seen: Vec<_> = (&feap).into_iter().copied().collect();
+        seen.sort_unstable();
+        let expected: Vec<_> = [-1].into_iter().chain(1..300).filter(|&v| v != 200)
+            .chain([1000]).collect();
+        assert_eq!(seen, expected);
+        assert_eq!(FibHeap::<i32>::new().iter().count(), 0);
+    }
+
+    #[test]
+    fn drain_keeps_buffers() {
+        let mut feap: FibHeap<_> = (0..300).rev().map(Box::new).collect();
+        let handle = feap.insert_with_handle(Box::new(300));
+        feap.extract_min();
+        let capacity = feap.head_list.capacity();
+
+        let mut drain = feap.drain();
+        assert_eq!(drain.len(), 300);
+        let mut some: Vec<_> = drain.by_ref().take(150).map(|b| *b).collect();
+        assert_eq!(drain.len(), 150);
+        drop(drain);
+
+        assert!(feap.is_empty());
+        assert!(feap.get(handle).is_none());
+        assert!(feap.head_list.capacity() >= capacity);
+        feap.validate();
+
+        feap.extend((0..3).map(Box::new));
+        let mut all: Vec<_> = feap.drain().map(|b| *b).collect();
+        all.sort_unstable();
+        assert_eq!(all, [0, 1, 2]);
+
+        some.sort_unstable();
+        some.dedup();
+        assert_eq!(some.len(), 150);
+    }
+
+    #[test]
+    fn drain_sorted_empties_the_heap() {
+        let mut feap: FibHeap<_> = (0..300).rev().collect();
+        let capacity = feap.head_list.capacity();
+
+        let first: Vec<_> = feap.drain_sorted().take(3).collect();
+        assert_eq!(first, [0, 1, 2]);
+        assert!(feap.is_empty());
+        assert_eq!(feap.head_list.capacity(), capacity);
+
+        feap.extend([7, 4, 9]);
+        let mut drain = feap.drain_sorted();
+        assert_eq!(drain.len(), 3);
+        assert!(drain.by_ref().eq([4, 7, 9]));
+        assert_eq!(drain.next(), None);
+    }
+
+    #[test]
+    fn drain_while_stops_at_the_first_failure() {
+        let mut feap: FibHeap<_> = (0..300).rev().collect();
+
+        let due: Vec<_> = feap.drain_while(|&v| v < 5).collect();
+        assert_eq!(due, [0, 1, 2, 3, 4]);
+        assert_eq!(feap.get_min(), Some(&5));
+
+        assert_eq!(feap.drain_while(|&v| v < 100).next(), Some(5));
+        assert_eq!(feap.len(), 294);
+
+        {
+            let mut drain = feap.drain_while(|&v| v % 2 == 0);
+            assert_eq!(drain.next(), Some(6));
+            assert_eq!(drain.next(), None);
+            assert_eq!(drain.size_hint(), (0, Some(0)));
+        }
+        assert_eq!(feap.get_min(), Some(&7));
+    }
+
+    #[test]
+    fn into_iter_sorted_is_lazy() {
+        let feap: FibHeap<_> = (0..300).rev().collect();
+        let mut iter = feap.into_iter_sorted();
+        assert_eq!(iter.len(), 300);
+
+        let first: Vec<_> = iter.by_ref().take(3).collect();
+        assert_eq!(first, [0, 1, 2]);
+        assert_eq!(iter.len(), 297);
+        assert_eq!(iter.heap.get_min(), Some(&3));
+
+        assert!(iter.by_ref().eq(3..300));
+        assert_eq!(iter.next(), None);
+    }
+}
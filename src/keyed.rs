@@ -0,0 +1,103 @@
+//! A wrapper which orders values by a separate key, for payloads which can
+//! not or should not be compared themselves.
+
+use core::cmp::Ordering;
+
+/// A value stored together with the key it is ordered by. Comparisons only
+/// look at the key, so the value does not have to implement [`PartialOrd`].
+/// This allows heaps of trait objects, which are ordered by a priority method
+/// of the trait instead of the objects themselves.
+///
+/// ```rust
+/// use feap::{FibHeap, Keyed};
+///
+/// trait Job {
+///     fn priority(&self) -> u32;
+///     fn name(&self) -> String;
+/// }
+///
+/// struct Backup;
+/// struct Alert(u32);
+///
+/// impl Job for Backup {
+///     fn priority(&self) -> u32 { 10 }
+///     fn name(&self) -> String { "backup".into() }
+/// }
+///
+/// impl Job for Alert {
+///     fn priority(&self) -> u32 { self.0 }
+///     fn name(&self) -> String { format!("alert {}", self.0) }
+/// }
+///
+/// let mut jobs: FibHeap<Keyed<u32, Box<dyn Job>>> = FibHeap::new();
+/// for job in [Box::new(Backup) as Box<dyn Job>, Box::new(Alert(1)), Box::new(Alert(20))] {
+///     jobs.insert(Keyed::by_key(job, |j| j.priority()));
+/// }
+///
+/// let order: Vec<_> = std::iter::from_fn(|| jobs.extract_min())
+///     .map(|job| job.value.name())
+///     .collect();
+/// assert_eq!(order, ["alert 1", "backup", "alert 20"]);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Keyed<K, V> {
+    /// The key the value is ordered by.
+    pub key: K,
+
+    /// The payload.
+    pub value: V,
+}
+
+impl<K, V> Keyed<K, V> {
+    /// Create a new [`Keyed`] from a key and a value.
+    pub fn new(key: K, value: V) -> Self {
+        Self { key, value }
+    }
+
+    /// Create a new [`Keyed`] whose key is derived from the value, for example
+    /// by a `priority()` method. The key is computed once here and not
+    /// updated if the value changes later.
+    pub fn by_key(value: V, key: impl FnOnce(&V) -> K) -> Self {
+        Self { key: key(&value), value }
+    }
+
+    /// `into_value` discards the key and returns the value.
+    pub fn into_value(self) -> V {
+        self.value
+    }
+}
+
+impl<K: PartialEq, V> PartialEq for Keyed<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: PartialOrd, V> PartialOrd for Keyed<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.key.partial_cmp(&other.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FibHeap;
+    use super::Keyed;
+
+    /// Closures can not be compared at all.
+    type Callback = Box<dyn Fn() -> &'static str>;
+
+    #[test]
+    fn orders_by_key_only() {
+        let mut heap: FibHeap<Keyed<i32, Callback>> = FibHeap::new();
+        heap.insert(Keyed::new(3, Box::new(|| "c")));
+        heap.insert(Keyed::new(1, Box::new(|| "a")));
+        heap.insert(Keyed::new(2, Box::new(|| "b")));
+
+        let order: Vec<_> = std::iter::from_fn(|| heap.extract_min())
+            .map(|k| (k.into_value())())
+            .collect();
+        assert_eq!(order, ["a", "b", "c"]);
+        assert!(Keyed::new(1, "x") == Keyed::new(1, "y"));
+    }
+}
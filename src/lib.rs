@@ -17,11 +17,69 @@
 //! feap.insert(10);
 //! feap.insert(5);
 //! assert_eq!(feap.get_min(), Some(&5));
-//! feap.decrease_key(10, 2);
+//! feap.decrease_key(&10, 2);
 //! assert_eq!(feap.get_min(), Some(&2));
 //! ```
 
+use core::borrow::Borrow;
+use core::cmp::{Ordering, Reverse};
+use core::ops::{Add, AddAssign};
 use core::ptr;
+use core::task::Poll;
+use std::alloc::{alloc, dealloc, Layout};
+use std::collections::{BinaryHeap, TryReserveError};
+
+mod aging;
+pub use aging::AgingQueue;
+
+mod error;
+pub use error::FeapError;
+
+mod event_queue;
+pub use event_queue::{EventQueue, PopUntil};
+
+#[cfg(any(test, feature = "async"))]
+mod edf;
+#[cfg(any(test, feature = "async"))]
+pub use edf::{EdfExecutor, TaskId};
+
+mod expiry;
+pub use expiry::ExpiryIndex;
+
+#[cfg(any(test, feature = "io"))]
+mod merge;
+#[cfg(any(test, feature = "io"))]
+pub use merge::ExternalMerge;
+
+mod iter;
+pub use iter::{Drain, DrainSorted, DrainWhile, IntoIterSorted, Iter};
+
+mod keyed;
+pub use keyed::Keyed;
+
+mod peek;
+pub use peek::PeekMut;
+
+mod multi_key;
+pub use multi_key::MultiKey;
+
+mod quantile;
+pub use quantile::QuantileTracker;
+
+mod scheduler;
+pub use scheduler::{ClassedScheduler, Policy};
+
+mod timer_wheel;
+pub use timer_wheel::TimerWheel;
+
+mod window;
+pub use window::WindowedMin;
+
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+#[cfg(kani)]
+mod verification;
 
 /// The maximum allowed degree of a tree.
 const MAX_DEGREE: usize = 0x100;
@@ -35,6 +93,59 @@ const CONSOLIDATION_THRESHOLD: usize = 100;
 /// Wrapper type around a mutable reference to a [`Node`].
 type Link<T> = *mut Node<T>;
 
+/// A function which wipes a value before it is dropped inside the heap, set
+/// by [`set_zeroize`](FibHeap::set_zeroize).
+type Wiper<T> = Option<fn(&mut T)>;
+
+/// A function which gives the weight of a value for
+/// [`pop_weighted_random`](FibHeap::pop_weighted_random), set by
+/// [`set_weight`](FibHeap::set_weight).
+type Weigher<T> = Option<fn(&T) -> f64>;
+
+/// The [`slot`](Node::slot) of a node which has no [`NodeHandle`].
+const NO_SLOT: u32 = u32::MAX;
+
+/// An opaque token for a value inserted with
+/// [`insert_with_handle`](FibHeap::insert_with_handle), which finds it again
+/// without searching the heap.
+///
+/// A handle stays valid until its value leaves the heap, by being extracted,
+/// cleared, or moved to another heap by a meld or a split. Handles that are no
+/// longer valid are rejected, also if their slot is reused for a newer
+/// value. Using a handle with a heap other than the one that issued it
+/// refers to an unrelated value of that heap, or to none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeHandle {
+    /// The index into [`slots`](FibHeap::slots).
+    slot: u32,
+
+    /// The generation the slot had when the handle was issued.
+    generation: u32,
+
+    /// The [`epoch`](FibHeap::epoch) of the heap when the handle was issued.
+    epoch: u32,
+}
+
+/// An entry of the handle table of a heap.
+struct Slot<T> {
+    /// The node the slot belongs to, or null if the slot is free.
+    node: Link<T>,
+
+    /// Counts how often the slot was reused, so old handles can be told apart.
+    generation: u32,
+}
+
+/// A root list of a heap which was melded into another one, see
+/// [`melded`](FibHeap::melded). Like the nodes, the entries are linked by raw
+/// pointers, so the heap can keep one to the last entry.
+struct Melded<T> {
+    /// The roots of the melded heap.
+    roots: Vec<Link<T>>,
+
+    /// The root list melded after this one, or null if it is the last.
+    next: *mut Melded<T>,
+}
+
 /// A node in the tree which holds the actual value, links to its parent and
 /// children and additional information of the node.
 #[derive(Debug)]
@@ -54,6 +165,15 @@ struct Node<T> {
     /// this node has lost a child already.
     marked: bool,
 
+    /// The index of the entry in [`slots`](FibHeap::slots) of this node, or
+    /// [`NO_SLOT`] if it was inserted without a [`NodeHandle`].
+    slot: u32,
+
+    /// The sum of the weights of this node and all of its descendants, if the
+    /// heap has a [`set_weight`](FibHeap::set_weight) function. Otherwise it
+    /// is not kept up to date.
+    weight: f64,
+
     /// The value of this node. The value can only be accessed via the 
     /// [`get_min`](FibHeap::get_min) or [`extract_min`](FibHeap::extract_min)
     /// methods, and not directly accessed, because if it can be changed, we
@@ -68,27 +188,329 @@ impl<T: PartialOrd> Node<T> {
             children: Vec::new(),
             degree:   0,
             marked:   false,
+            slot:     NO_SLOT,
+            weight:   0.0,
             val
         }
     }
 }
 
+/// Which of two equal roots becomes the parent when they are linked during a
+/// consolidation. This decides which of equal values stays the minimum and
+/// how the trees are shaped, but never affects the order of values which
+/// compare differently. A secondary order between equal keys belongs into the
+/// [`PartialOrd`] of the values instead, for example as a tuple in the key of
+/// a [`Keyed`].
+///
+/// ```rust
+/// use feap::{FibHeap, Keyed, TieBreak};
+///
+/// let mut feap = FibHeap::new();
+/// feap.set_tie_break(TieBreak::Last);
+/// feap.insert(Keyed::new(1, "first"));
+/// feap.insert(Keyed::new(1, "second"));
+/// feap.insert(Keyed::new(0, "zero"));
+///
+/// feap.extract_min();
+/// assert_eq!(feap.get_min().map(|k| k.value), Some("second"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieBreak {
+    /// The root which was reached first in the
+    /// [`head_list`](FibHeap::head_list) stays a root. As the roots are kept
+    /// in the order they were added, this mostly favours older values.
+    #[default]
+    First,
+
+    /// The root which was reached last stays a root.
+    Last,
+}
+
 /// The actual fibonacci heap structure.
-#[derive(Clone)]
 pub struct FibHeap<T: PartialOrd> {
     /// A pointer to the current minimum for convenient and faster access.
     min: Link<T>,
 
-    /// The list of all the roots of trees currently in this fibonacci heap.
+    /// The list of all the roots of trees currently in this fibonacci heap,
+    /// apart from the ones still in [`melded`](FibHeap::melded).
     head_list: Vec<Link<T>>,
 
+    /// The root lists of the heaps melded into this one, in the order they
+    /// were melded. They are only moved into the
+    /// [`head_list`](FibHeap::head_list) by
+    /// [`join_roots`](FibHeap::join_roots) once the roots are needed, so
+    /// melding does not copy them. Null if there are none.
+    melded: *mut Melded<T>,
+
+    /// The last entry of [`melded`](FibHeap::melded), or null if it is
+    /// empty.
+    melded_tail: *mut Melded<T>,
+
     /// A list to temporarily save new roots during consolidation.
     root_list: Vec<Link<T>>,
+
+    /// An empty list which
+    /// [`extract_min_budgeted`](FibHeap::extract_min_budgeted) swaps with the
+    /// [`head_list`](FibHeap::head_list) while it walks the roots, so polling
+    /// moves them between the same two buffers instead of allocating.
+    scratch_list: Vec<Link<T>>,
+
+    /// The number of values in the heap.
+    len: usize,
+
+    /// The number of values which are kept without a node, which only
+    /// happens for a zero-sized `T`. All values of such a type are alike, so
+    /// the ones inserted without a [`NodeHandle`] are just counted, and are
+    /// read from a dangling pointer when they are handed out, as a [`Vec`] of
+    /// them does. They are part of [`len`](FibHeap::len) and are extracted
+    /// before the values in nodes.
+    units: usize,
+
+    /// The nodes of all valid [`NodeHandle`]s, indexed by their slot.
+    slots: Vec<Slot<T>>,
+
+    /// The indices of the free entries of [`slots`](FibHeap::slots).
+    free_slots: Vec<u32>,
+
+    /// Counts how often all handles were invalidated at once by emptying
+    /// [`slots`](FibHeap::slots), so the handles issued before can be told
+    /// apart from the ones for the reused slots.
+    epoch: u32,
+
+    /// How equal roots are linked during consolidation.
+    tie_break: TieBreak,
+
+    /// Whether a [`head_list`](FibHeap::head_list) longer than the
+    /// [`CONSOLIDATION_THRESHOLD`] is consolidated on inserts.
+    consolidate_on_insert: bool,
+
+    /// The number of roots at the start of the
+    /// [`head_list`](FibHeap::head_list) which an interrupted
+    /// [`extract_min_budgeted`](FibHeap::extract_min_budgeted) has already
+    /// linked. Any reordering of the [`head_list`](FibHeap::head_list) resets
+    /// it to zero.
+    linked_prefix: usize,
+
+    /// Nodes kept by [`clear_and_recycle`](FibHeap::clear_and_recycle) for
+    /// later inserts. Their values are dropped and their lists of children
+    /// freed, only the allocations are left.
+    spare: Vec<Link<T>>,
+
+    /// Wipes the values which are dropped inside the heap.
+    wipe: Wiper<T>,
+
+    /// Weighs the values for
+    /// [`pop_weighted_random`](FibHeap::pop_weighted_random).
+    weigh: Weigher<T>,
 }
 
 impl<T: PartialOrd> Drop for FibHeap<T> {
     fn drop(&mut self) {
         self.clear();
+        for node in self.spare.drain(..) {
+            unsafe { dealloc(node.cast(), Layout::new::<Node<T>>()) };
+        }
+    }
+}
+
+impl<T: PartialOrd + Clone> Clone for FibHeap<T> {
+    /// Deep copies every tree of the heap, so the clone owns its own nodes.
+    fn clone(&self) -> Self {
+        let mut new = Self::new();
+        new.clone_from(self);
+        new
+    }
+
+    /// Reuses the nodes of this heap for the copy, with their values updated
+    /// by [`Clone::clone_from`]. Nodes are handed out in the same order in
+    /// which they were taken, so copying a heap of the same shape again does
+    /// not allocate at all.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut template = FibHeap::new();
+    /// template.insert(String::from("b"));
+    /// template.insert(String::from("a"));
+    ///
+    /// let mut scratch = template.clone();
+    /// scratch.extract_min();
+    /// scratch.clone_from(&template);
+    /// assert_eq!(scratch.get_min().map(String::as_str), Some("a"));
+    /// ```
+    fn clone_from(&mut self, source: &Self) {
+        // Thread all nodes into a list through their parent links, in the
+        // order in which clone_tree visits them.
+        self.drop_units();
+        self.join_roots();
+        let mut pool = ptr::null_mut();
+        let mut tail: *mut Link<T> = &mut pool;
+        for t in self.head_list.drain(..) {
+            tail = pool_tree(t, tail);
+        }
+        unsafe { *tail = ptr::null_mut(); }
+
+        self.min = ptr::null_mut();
+        self.linked_prefix = 0;
+        self.len = source.len;
+        self.release_slots();
+        self.tie_break = source.tie_break;
+        self.consolidate_on_insert = source.consolidate_on_insert;
+        self.wipe = source.wipe;
+        self.weigh = source.weigh;
+        for &t in source.root_lists().flatten() {
+            let root = clone_tree(t, ptr::null_mut(), &mut pool, self.wipe);
+            if t == source.min {
+                self.min = root;
+            }
+            self.head_list.push(root);
+        }
+        for _ in 0..source.units {
+            core::mem::forget(T::clone(unsafe { unit() }));
+        }
+        self.units = source.units;
+
+        // Free the nodes which were not needed.
+        while !pool.is_null() {
+            let node = pool;
+            unsafe {
+                pool = (*node).parent;
+                free_node(node, self.wipe);
+            }
+        }
+        self.check_invariants();
+    }
+}
+
+impl<T: PartialOrd> Add for FibHeap<T> {
+    type Output = Self;
+
+    /// Melds both heaps into one with [`meld`](FibHeap::meld). The nodes of
+    /// `rhs` are moved over, not copied.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut a = FibHeap::new();
+    /// a.insert(3);
+    /// let mut b = FibHeap::new();
+    /// b.insert(1);
+    ///
+    /// let mut merged = a + b;
+    /// assert_eq!(merged.extract_min(), Some(1));
+    /// assert_eq!(merged.extract_min(), Some(3));
+    /// ```
+    fn add(mut self, rhs: Self) -> Self {
+        self.meld(rhs);
+        self
+    }
+}
+
+impl<T: PartialOrd> AddAssign for FibHeap<T> {
+    /// Melds `rhs` into this heap with [`meld`](FibHeap::meld).
+    fn add_assign(&mut self, rhs: Self) {
+        self.meld(rhs);
+    }
+}
+
+impl<T: PartialOrd> Extend<T> for FibHeap<T> {
+    /// Inserts all values as new roots and consolidates at most once at the
+    /// end, instead of whenever the [`CONSOLIDATION_THRESHOLD`] is crossed.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// feap.insert(5);
+    /// feap.extend([3, 8, 1]);
+    /// assert_eq!(feap.len(), 4);
+    /// assert_eq!(feap.get_min(), Some(&1));
+    /// ```
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        if !Self::ZERO_SIZED {
+            self.head_list.reserve(iter.size_hint().0);
+        }
+        for val in iter {
+            self.push_value(val);
+        }
+        if self.consolidate_on_insert && self.head_list.len() > CONSOLIDATION_THRESHOLD {
+            self.consolidate(true);
+        }
+        self.check_invariants();
+    }
+}
+
+impl<'a, T: PartialOrd + Copy + 'a> Extend<&'a T> for FibHeap<T> {
+    /// Copies all values into the heap like the [`Extend`] of owned values.
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T: PartialOrd> FromIterator<T> for FibHeap<T> {
+    /// Builds a heap from all values with a single consolidation at the end,
+    /// see [`extend`](FibHeap::extend).
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap: FibHeap<_> = [4, 2, 7].into_iter().collect();
+    /// assert_eq!(feap.extract_min(), Some(2));
+    /// ```
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut feap = Self::new();
+        feap.extend(iter);
+        feap
+    }
+}
+
+impl<T: PartialOrd> From<Vec<T>> for FibHeap<T> {
+    /// See [`from_vec`](FibHeap::from_vec).
+    fn from(vec: Vec<T>) -> Self {
+        Self::from_vec(vec)
+    }
+}
+
+impl<T: PartialOrd> From<BinaryHeap<T>> for FibHeap<T> {
+    /// Moves all values of a [`BinaryHeap`] into a new heap in linear time, see
+    /// [`from_vec`](FibHeap::from_vec). A [`BinaryHeap`] yields the largest
+    /// value first, so wrap the values in [`Reverse`] to keep that order.
+    ///
+    /// ```rust
+    /// use std::collections::BinaryHeap;
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::from(BinaryHeap::from([2, 9, 4]));
+    /// assert_eq!(feap.extract_min(), Some(2));
+    /// ```
+    fn from(heap: BinaryHeap<T>) -> Self {
+        Self::from_vec(heap.into_vec())
+    }
+}
+
+impl<T: Ord> From<FibHeap<T>> for BinaryHeap<T> {
+    /// Moves all values of the heap into a [`BinaryHeap`] in linear time.
+    ///
+    /// ```rust
+    /// use std::collections::BinaryHeap;
+    /// use feap::FibHeap;
+    ///
+    /// let feap: FibHeap<_> = [2, 9, 4].into_iter().collect();
+    /// let mut heap = BinaryHeap::from(feap);
+    /// assert_eq!(heap.pop(), Some(9));
+    /// ```
+    fn from(feap: FibHeap<T>) -> Self {
+        BinaryHeap::from(feap.into_values())
+    }
+}
+
+impl<'a, T: PartialOrd> IntoIterator for &'a FibHeap<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
     }
 }
 
@@ -99,6 +521,10 @@ impl<T: PartialOrd> Default for FibHeap<T> {
 }
 
 impl<T: PartialOrd> FibHeap<T> {
+    /// Whether `T` is zero-sized, so its values are counted in
+    /// [`units`](FibHeap::units) instead of being put into nodes.
+    const ZERO_SIZED: bool = core::mem::size_of::<T>() == 0;
+
     /// Create a new [`FibHeap`] object. The lists are preallocated with some
     /// capacity to save on some ms for not needing to call `realloc`.
     /// 
@@ -110,19 +536,130 @@ impl<T: PartialOrd> FibHeap<T> {
         Self {
             min: ptr::null_mut(),
             head_list: Vec::with_capacity(CONSOLIDATION_THRESHOLD),
+            melded: ptr::null_mut(),
+            melded_tail: ptr::null_mut(),
             root_list: vec![ptr::null_mut(); MAX_DEGREE],
+            scratch_list: Vec::new(),
+            len: 0,
+            units: 0,
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+            epoch: 0,
+            tie_break: TieBreak::First,
+            consolidate_on_insert: true,
+            linked_prefix: 0,
+            spare: Vec::new(),
+            wipe: None,
+            weigh: None,
         }
     }
 
-    /// The recursive clearing method which drops the references to the children
-    /// of a given node.
-    fn _clear(&self, node: Link<T>) {
-        unsafe {
-            let children = &(*node).children;
-            for &c in children {
-                self._clear(c);
+    /// `from_vec` builds a heap from all values of `vec` in linear time. Every
+    /// value becomes a root and the [`head_list`](FibHeap::head_list) grows at
+    /// most once to the exact length, the consolidation is left to the first
+    /// [`extract_min`](FibHeap::extract_min). The nodes themselves are
+    /// still allocated one by one, since each of them is freed on its own.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::from_vec(vec![3, 1, 2]);
+    /// assert_eq!(feap.len(), 3);
+    /// assert_eq!(feap.extract_min(), Some(1));
+    /// ```
+    pub fn from_vec(vec: Vec<T>) -> Self {
+        let mut feap = Self::new();
+        if !Self::ZERO_SIZED {
+            feap.head_list.reserve_exact(vec.len());
+        }
+        for val in vec {
+            feap.push_value(val);
+        }
+        feap.check_invariants();
+        feap
+    }
+
+    /// `set_tie_break` sets which of two equal roots becomes the parent when
+    /// they are linked. The default is [`TieBreak::First`].
+    pub fn set_tie_break(&mut self, tie_break: TieBreak) {
+        self.tie_break = tie_break;
+    }
+
+    /// `set_insert_consolidation` sets whether inserts consolidate the heap
+    /// once the [`head_list`](FibHeap::head_list) grows beyond the
+    /// [`CONSOLIDATION_THRESHOLD`], which is the default. When disabled,
+    /// every insert is O(1) as in the textbook structure and all the work is
+    /// deferred to the next [`extract_min`](FibHeap::extract_min), which then
+    /// takes time linear in the number of inserts since the last one. This
+    /// suits workloads which insert in large batches and extract rarely.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// feap.set_insert_consolidation(false);
+    /// for i in (0..1000).rev() {
+    ///     feap.insert(i);
+    /// }
+    /// assert_eq!(feap.extract_min(), Some(0));
+    /// ```
+    pub fn set_insert_consolidation(&mut self, enabled: bool) {
+        self.consolidate_on_insert = enabled;
+    }
+
+    /// `set_zeroize` sets whether values which are dropped inside the heap
+    /// are wiped with [`Zeroize`](zeroize::Zeroize) first. This covers
+    /// [`clear`](FibHeap::clear), [`clear_and_recycle`](FibHeap::clear_and_recycle),
+    /// [`retain`](FibHeap::retain), drains which are not consumed, values
+    /// overwritten by [`clone_from`](Clone::clone_from) and dropping the heap,
+    /// and also wipes the memory the values own, like the buffer of a
+    /// [`Vec`] or a [`String`]. Extracted values are handed out as they are.
+    /// The memory of the nodes themselves is wiped with the `zeroize` feature
+    /// either way. Heaps split off by [`split_by`](FibHeap::split_by),
+    /// cloned or turned around by [`into_max_heap`](FibHeap::into_max_heap)
+    /// keep the setting.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut keys = FibHeap::new();
+    /// keys.set_zeroize(true);
+    /// keys.insert(b"secret".to_vec());
+    /// keys.clear();
+    /// ```
+    #[cfg(feature = "zeroize")]
+    pub fn set_zeroize(&mut self, enabled: bool)
+    where
+        T: zeroize::Zeroize,
+    {
+        self.wipe = if enabled { Some(zeroize::Zeroize::zeroize) } else { None };
+    }
+
+    /// `set_weight` sets the function which weighs the values for
+    /// [`pop_weighted_random`](FibHeap::pop_weighted_random), or removes it.
+    /// Weights which are not positive, including NaN, count as zero.
+    ///
+    /// While a function is set, every node keeps the sum of the weights of
+    /// its subtree. Setting one walks the whole heap once to compute them.
+    /// Afterwards linking trees updates the sums in O(1), but every cut also
+    /// updates the sums of the ancestors of the cut node, which makes
+    /// [`decrease_key`](FibHeap::decrease_key) and removals take time in the
+    /// depth of the node instead of amortized O(1).
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut replay: FibHeap<_> = [3, 1, 2].into_iter().collect();
+    /// replay.set_weight(Some(|&v| f64::from(v)));
+    /// assert!(replay.pop_weighted_random(|| 0.5).is_some());
+    /// ```
+    pub fn set_weight(&mut self, weight: Option<fn(&T) -> f64>) {
+        self.weigh = weight;
+        if let Some(weigh) = weight {
+            self.join_roots();
+            for &t in &self.head_list {
+                weigh_tree(t, weigh);
             }
-            drop(Box::from_raw(node));
         }
     }
 
@@ -141,11 +678,169 @@ impl<T: PartialOrd> FibHeap<T> {
     /// assert_eq!(feap.get_min(), None);
     /// ```
     pub fn clear(&mut self) {
-        for &t in &self.head_list {
-            self._clear(t);
+        self.drop_units();
+        self.join_roots();
+        for t in self.head_list.drain(..) {
+            free_tree(t, self.wipe);
+        }
+        self.min = ptr::null_mut();
+        self.linked_prefix = 0;
+        self.len = 0;
+        self.release_slots();
+    }
+
+    /// `clear_and_recycle` removes all values like [`clear`](FibHeap::clear),
+    /// but keeps the nodes for later inserts instead of deallocating them.
+    /// This saves an allocation per insert for heaps which are filled and
+    /// cleared over and over again. The kept nodes are only freed when the
+    /// heap is dropped, so they take as much memory as the heap had values.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap: FibHeap<_> = (0..100).collect();
+    /// feap.clear_and_recycle();
+    /// assert!(feap.is_empty());
+    ///
+    /// // These reuse the nodes of the cleared values.
+    /// feap.extend(0..100);
+    /// ```
+    pub fn clear_and_recycle(&mut self) {
+        self.drop_units();
+        self.spare.reserve(self.len);
+        self.join_roots();
+        for t in self.head_list.drain(..) {
+            recycle_tree(t, &mut self.spare, self.wipe);
         }
-        self.head_list.clear();
         self.min = ptr::null_mut();
+        self.linked_prefix = 0;
+        self.len = 0;
+        self.release_slots();
+    }
+
+    /// `len` returns the number of values in the heap.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// feap.insert(3);
+    /// feap.insert(3);
+    /// assert_eq!(feap.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// `is_empty` returns whether the heap holds no values.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// assert!(feap.is_empty());
+    /// feap.insert(1);
+    /// assert!(!feap.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// `reserve` makes room for at least `additional` more values, so
+    /// inserting that many values does not allocate. The
+    /// [`head_list`](FibHeap::head_list) is grown, and the missing nodes are
+    /// allocated up front and kept like the ones of
+    /// [`clear_and_recycle`](FibHeap::clear_and_recycle). The table of
+    /// [`NodeHandle`]s grows on its own as handles are issued, see
+    /// [`reserve_handles`](FibHeap::reserve_handles) to grow it up front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// feap.set_insert_consolidation(false);
+    /// feap.reserve(1000);
+    /// for i in 0..1000 {
+    ///     feap.insert(i);
+    /// }
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.head_list.reserve(additional);
+        let nodes = additional.saturating_sub(self.spare.len());
+        self.spare.reserve(nodes);
+        for _ in 0..nodes {
+            let node = Box::<Node<T>>::new_uninit();
+            self.spare.push(Box::into_raw(node).cast());
+        }
+    }
+
+    /// `try_reserve` is [`reserve`](FibHeap::reserve), but returns an error
+    /// instead of aborting if the memory can not be allocated. This covers
+    /// the nodes as well, so the reserved number of inserts can not fail to
+    /// allocate. On an error, whatever was reserved before it is kept.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::<u32>::new();
+    /// assert!(feap.try_reserve(100).is_ok());
+    /// assert!(feap.try_reserve(usize::MAX).is_err());
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.head_list.try_reserve(additional)?;
+        let nodes = additional.saturating_sub(self.spare.len());
+        self.spare.try_reserve(nodes)?;
+        for _ in 0..nodes {
+            let mut node = unsafe { alloc(Layout::new::<Node<T>>()) }.cast::<Node<T>>();
+            if node.is_null() {
+                // The error has no public constructor, so it comes from a
+                // vector with room for one node, which has the same layout.
+                // Should that get the memory after all, it is the node.
+                let mut vec = Vec::<Node<T>>::new();
+                vec.try_reserve_exact(1)?;
+                node = core::mem::ManuallyDrop::new(vec).as_mut_ptr();
+            }
+            self.spare.push(node);
+        }
+        Ok(())
+    }
+
+    /// `reserve_handles` makes room for at least `additional` more valid
+    /// [`NodeHandle`]s, so issuing that many with
+    /// [`insert_with_handle`](FibHeap::insert_with_handle) does not grow the
+    /// table of handles. Together with [`reserve`](FibHeap::reserve) such
+    /// inserts do not allocate at all.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// feap.reserve(100);
+    /// feap.reserve_handles(100);
+    /// let handles: Vec<_> = (0..100).map(|i| feap.insert_with_handle(i)).collect();
+    /// assert_eq!(feap.get(handles[42]), Some(&42));
+    /// ```
+    pub fn reserve_handles(&mut self, additional: usize) {
+        let slots = additional.saturating_sub(self.free_slots.len());
+        self.slots.reserve(slots);
+        self.free_slots.reserve(self.slots.len() + slots - self.free_slots.len());
+    }
+
+    /// `try_reserve_handles` is [`reserve_handles`](FibHeap::reserve_handles),
+    /// but returns an error instead of aborting if the memory can not be
+    /// allocated.
+    pub fn try_reserve_handles(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let slots = additional.saturating_sub(self.free_slots.len());
+        self.slots.try_reserve(slots)?;
+        self.free_slots.try_reserve(self.slots.len() + slots - self.free_slots.len())
     }
 
     /// `get_min` returns an immutable reference to the value of the minimum if
@@ -164,6 +859,9 @@ impl<T: PartialOrd> FibHeap<T> {
     /// assert_eq!(feap.get_min(), Some(&4));
     /// ```
     pub fn get_min(&self) -> Option<&T> {
+        if self.units > 0 {
+            return Some(unsafe { unit() });
+        }
         unsafe { self.min.as_ref().map(|m| &m.val) }
     }
 
@@ -171,7 +869,10 @@ impl<T: PartialOrd> FibHeap<T> {
     /// [`head_list`](FibHeap::head_list) (updating the [`min`](FibHeap::min)
     /// if needed). Additionally to keep the [`head_list`](FibHeap::head_list)
     /// small, if the length of the [`head_list`](FibHeap::head_list) becomes
-    /// larger than the [`CONSOLIDATION_THRESHOLD`] a consolidation will happen.
+    /// larger than the [`CONSOLIDATION_THRESHOLD`] a consolidation will happen,
+    /// unless disabled with
+    /// [`set_insert_consolidation`](FibHeap::set_insert_consolidation).
+    /// Values of a zero-sized type are only counted, without a node.
     /// 
     /// ```rust
     /// use feap::FibHeap;
@@ -183,29 +884,323 @@ impl<T: PartialOrd> FibHeap<T> {
     /// assert_eq!(feap.get_min(), Some(&10));
     /// ```
     pub fn insert(&mut self, val: T) {
-        let new = Box::into_raw(Box::new(Node::new(val)));
-        self.insert_node(new);
-        if self.head_list.len() > CONSOLIDATION_THRESHOLD {
-            self.consolidate(true);
+        if Self::ZERO_SIZED {
+            self.push_value(val);
+            self.check_invariants();
+            return;
         }
+        let new = self.alloc_node(val);
+        self.insert_new(new);
     }
 
-    /// An internal helper function which updates the minimum if necessary and
-    /// insert a node into the [`head_list`](FibHeap::head_list).
-    fn insert_node(&mut self, new: Link<T>) {
+    /// `insert_with_handle` inserts `val` like [`insert`](FibHeap::insert) and
+    /// returns a [`NodeHandle`] for it, which finds the value in O(1) for as
+    /// long as it stays in the heap.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// let a = feap.insert_with_handle("a");
+    /// let b = feap.insert_with_handle("b");
+    ///
+    /// assert_eq!(feap.get(b), Some(&"b"));
+    /// assert_eq!(feap.extract_min(), Some("a"));
+    /// assert_eq!(feap.get(a), None);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX - 1` handles would be valid at once.
+    pub fn insert_with_handle(&mut self, val: T) -> NodeHandle {
+        let new = self.alloc_node(val);
+        let slot = match self.free_slots.pop() {
+            Some(slot) => slot,
+            None => {
+                let slot = u32::try_from(self.slots.len()).ok()
+                    .filter(|&s| s != NO_SLOT)
+                    .expect("too many node handles");
+                self.slots.push(Slot { node: ptr::null_mut(), generation: 0 });
+                slot
+            }
+        };
+        let entry = &mut self.slots[slot as usize];
+        entry.node = new;
+        let handle = NodeHandle { slot, generation: entry.generation, epoch: self.epoch };
+        unsafe { (*new).slot = slot; }
+        self.insert_new(new);
+        handle
+    }
+
+    /// `get` returns the value of `handle`, or `None` if the handle is no
+    /// longer valid.
+    pub fn get(&self, handle: NodeHandle) -> Option<&T> {
+        self.handle_node(handle).map(|node| unsafe { &(*node).val })
+    }
+
+    /// `handle_node` returns the node of `handle` if the handle is valid.
+    fn handle_node(&self, handle: NodeHandle) -> Option<Link<T>> {
+        if handle.epoch != self.epoch {
+            return None;
+        }
+        self.slots.get(handle.slot as usize)
+            .filter(|s| s.generation == handle.generation && !s.node.is_null())
+            .map(|s| s.node)
+    }
+
+    /// `release_slot` invalidates the handle of `node`, if it has one, and
+    /// frees its slot for reuse. A node keeps the slot it had in a heap it
+    /// was melded from, which only counts if the slot points back to it.
+    fn release_slot(&mut self, node: Link<T>) {
         unsafe {
-            if self.min.is_null() || (*new).val < (*self.min).val {
-                self.min = new;
+            let slot = (*node).slot;
+            (*node).slot = NO_SLOT;
+            match self.slots.get_mut(slot as usize) {
+                Some(entry) if entry.node == node => {
+                    entry.node = ptr::null_mut();
+                    entry.generation = entry.generation.wrapping_add(1);
+                    self.free_slots.push(slot);
+                }
+                _ => {}
             }
-            self.head_list.push(new);
         }
     }
 
-    /// `consolidate` will firstly (if called from 
-    /// [`extract_min`](FibHeap::extract_min)) put all children from the current
-    /// [`min`](FibHeap::min) node into the [`head_list`](FibHeap::head_list).
-    /// Then all trees in the [`head_list`](FibHeap::head_list) will be (if 
-    /// possible) paired with another tree of the same [`degree`](Node::degree).
+    /// `release_slots` invalidates all handles in O(1) by emptying the slots
+    /// and starting a new [`epoch`](FibHeap::epoch). The nodes are not
+    /// touched, their slots are told apart by
+    /// [`release_slot`](FibHeap::release_slot).
+    fn release_slots(&mut self) {
+        self.slots.clear();
+        self.free_slots.clear();
+        self.epoch = self.epoch.wrapping_add(1);
+    }
+
+    /// `alloc_node` creates a node for `val`, reusing a spare node if there
+    /// is one.
+    fn alloc_node(&mut self, val: T) -> Link<T> {
+        let mut node = Node::new(val);
+        if let Some(weigh) = self.weigh {
+            node.weight = own_weight(weigh, &node.val);
+        }
+        match self.spare.pop() {
+            Some(spare) => {
+                unsafe { ptr::write(spare, node) };
+                spare
+            }
+            None => Box::into_raw(Box::new(node)),
+        }
+    }
+
+    /// `push_value` adds `val` to the heap as a new root without
+    /// consolidating, or only counts it if `T` is zero-sized.
+    fn push_value(&mut self, val: T) {
+        if Self::ZERO_SIZED {
+            core::mem::forget(val);
+            self.units += 1;
+        } else {
+            let new = self.alloc_node(val);
+            self.insert_node(new);
+        }
+        self.len += 1;
+    }
+
+    /// `pop_unit` takes one of the [`units`](FibHeap::units) out of the heap,
+    /// if it counts any.
+    fn pop_unit(&mut self) -> Option<T> {
+        if self.units == 0 {
+            return None;
+        }
+        self.units -= 1;
+        self.len -= 1;
+        Some(unsafe { take_unit() })
+    }
+
+    /// `drop_units` drops all [`units`](FibHeap::units) of the heap. They
+    /// take no memory, so there is nothing to wipe.
+    fn drop_units(&mut self) {
+        while let Some(unit) = self.pop_unit() {
+            drop(unit);
+        }
+    }
+
+    /// `insert_new` adds a newly allocated node to the heap.
+    fn insert_new(&mut self, new: Link<T>) {
+        self.insert_node(new);
+        self.len += 1;
+        if self.consolidate_on_insert && self.head_list.len() > CONSOLIDATION_THRESHOLD {
+            self.consolidate(true);
+        }
+        self.check_invariants();
+    }
+
+    /// An internal helper function which updates the minimum if necessary and
+    /// insert a node into the [`head_list`](FibHeap::head_list).
+    fn insert_node(&mut self, new: Link<T>) {
+        unsafe {
+            if self.min.is_null() || (*new).val < (*self.min).val {
+                self.min = new;
+            }
+            self.head_list.push(new);
+        }
+    }
+
+    /// `meld` moves all values of `other` into this heap in O(1). The trees
+    /// are not linked and the root list of `other` is not even copied: it is
+    /// chained to the ones of the heaps melded before, and only joined into
+    /// the [`head_list`](FibHeap::head_list) by the next operation which
+    /// walks the roots, like [`extract_min`](FibHeap::extract_min), as part
+    /// of the work it does on them anyway. The smaller minimum is kept.
+    ///
+    /// The [`NodeHandle`]s of `other` become invalid, the ones of this heap
+    /// stay valid. The only exception to O(1) is a heap with a
+    /// [`set_weight`](FibHeap::set_weight) function, where the trees of
+    /// `other` are weighed again unless it has the same function.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut a = FibHeap::new();
+    /// a.insert(2);
+    /// let mut b = FibHeap::new();
+    /// b.insert(1);
+    /// b.insert(3);
+    ///
+    /// a.meld(b);
+    /// assert_eq!(a.len(), 3);
+    /// assert_eq!(a.extract_min(), Some(1));
+    /// ```
+    pub fn meld(&mut self, mut other: Self) {
+        self.append(&mut other);
+    }
+
+    /// `append` moves all values of `other` into this heap like
+    /// [`meld`](FibHeap::meld), but leaves `other` empty instead of consuming
+    /// it, so its handle slots and the rest of its allocations can be reused.
+    /// Only its root list moves along with the roots. The [`NodeHandle`]s of
+    /// `other` become invalid.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut all = FibHeap::new();
+    /// let mut batch = FibHeap::new();
+    /// for round in 0..3 {
+    ///     batch.insert(round);
+    ///     batch.insert(round + 10);
+    ///     all.append(&mut batch);
+    ///     assert!(batch.is_empty());
+    /// }
+    /// assert_eq!(all.len(), 6);
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        let units = core::mem::take(&mut other.units);
+        self.units += units;
+        self.len += units;
+        other.len -= units;
+        if other.min.is_null() {
+            return;
+        }
+        unsafe {
+            if self.min.is_null() || (*other.min).val < (*self.min).val {
+                self.min = other.min;
+            }
+        }
+        self.adopt_weights(other);
+        // The nodes keep the slots of other, which release_slot tells apart
+        // from the ones of this heap.
+        other.release_slots();
+
+        let chunk = Box::into_raw(Box::new(Melded {
+            roots: core::mem::take(&mut other.head_list),
+            next: other.melded,
+        }));
+        let tail = if other.melded_tail.is_null() { chunk } else { other.melded_tail };
+        other.melded = ptr::null_mut();
+        other.melded_tail = ptr::null_mut();
+        if self.melded_tail.is_null() {
+            self.melded = chunk;
+        } else {
+            unsafe { (*self.melded_tail).next = chunk };
+        }
+        self.melded_tail = tail;
+
+        self.len += other.len;
+        other.min = ptr::null_mut();
+        other.len = 0;
+        other.linked_prefix = 0;
+        self.check_invariants();
+    }
+
+    /// `adopt_weights` recomputes the weight sums of the trees of `other` with
+    /// the weight function of this heap, unless `other` already weighs its
+    /// values the same way or this heap does not weigh them at all.
+    fn adopt_weights(&self, other: &mut Self) {
+        if let Some(weigh) = self.weigh {
+            if !other.weigh.is_some_and(|w| ptr::fn_addr_eq(w, weigh)) {
+                other.join_roots();
+                for &t in &other.head_list {
+                    weigh_tree(t, weigh);
+                }
+                other.weigh = self.weigh;
+            }
+        }
+    }
+
+    /// `join_roots` moves the roots of all [`melded`](FibHeap::melded) heaps
+    /// into the [`head_list`](FibHeap::head_list), behind the roots already in
+    /// it. Every operation which takes the roots out of the
+    /// [`head_list`](FibHeap::head_list) or reorders it calls this first.
+    fn join_roots(&mut self) {
+        let mut next = self.melded;
+        self.melded = ptr::null_mut();
+        self.melded_tail = ptr::null_mut();
+        while !next.is_null() {
+            let mut chunk = unsafe { Box::from_raw(next) };
+            self.head_list.append(&mut chunk.roots);
+            next = chunk.next;
+        }
+    }
+
+    /// `root_lists` returns the [`head_list`](FibHeap::head_list) followed by
+    /// the root lists of the [`melded`](FibHeap::melded) heaps, for the
+    /// operations which only read the roots.
+    fn root_lists(&self) -> impl Iterator<Item = &[Link<T>]> {
+        let mut next = self.melded;
+        core::iter::once(&self.head_list[..]).chain(core::iter::from_fn(move || {
+            // The entries are owned by the heap, which is borrowed.
+            let chunk = unsafe { next.as_ref()? };
+            next = chunk.next;
+            Some(&chunk.roots[..])
+        }))
+    }
+
+    /// `union` melds `a` and `b` into one heap, which keeps the settings and
+    /// [`NodeHandle`]s of `a`.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut a = FibHeap::new();
+    /// a.insert(3);
+    /// a.insert(1);
+    /// let mut b = FibHeap::new();
+    /// b.insert(2);
+    ///
+    /// let mut all = FibHeap::union(a, b);
+    /// assert_eq!(all.extract_min(), Some(1));
+    /// assert_eq!(all.extract_min(), Some(2));
+    /// ```
+    pub fn union(mut a: Self, b: Self) -> Self {
+        a.meld(b);
+        a
+    }
+
+    /// `consolidate` will firstly (if called from 
+    /// [`extract_min`](FibHeap::extract_min)) put all children from the current
+    /// [`min`](FibHeap::min) node into the [`head_list`](FibHeap::head_list).
+    /// Then all trees in the [`head_list`](FibHeap::head_list) will be (if 
+    /// possible) paired with another tree of the same [`degree`](Node::degree).
     /// If there is such a pair, the one with the greater value of the two will 
     /// become a child of the one with the smaller value, which causes the 
     /// [`degree`](Node::degree) of the tree with the smaller value to increase
@@ -216,30 +1211,36 @@ impl<T: PartialOrd> FibHeap<T> {
             if self.min.is_null() {
                 return;
             }
+            self.join_roots();
 
             // Remove all children from min if we are not doing an insert
             if !insert_mode {
-                for &c in &(*self.min).children {
-                    self.head_list.push(c);
+                let children = core::mem::take(&mut (*self.min).children);
+                (*self.min).degree = 0;
+                for c in children {
                     (*c).parent = core::ptr::null_mut();
+                    self.head_list.push(c);
                 }
             }
 
-            // Merge trees
+            // Merge trees. The head_list is moved out while the nodes are
+            // relinked, so no borrow of it is alive while they are mutated.
             self.root_list.clear();
             self.root_list.resize(MAX_DEGREE, ptr::null_mut());
-            for &c in &self.head_list {
+            let mut head_list = core::mem::take(&mut self.head_list);
+            for c in head_list.drain(..) {
                 if insert_mode || c != self.min {
-                    let mut tmp = insert_root_list(c, &mut self.root_list);
+                    let mut tmp = insert_root_list(c, &mut self.root_list, self.tie_break);
                     while !tmp.is_null() {
-                        tmp = insert_root_list(tmp, &mut self.root_list);
+                        tmp = insert_root_list(tmp, &mut self.root_list, self.tie_break);
                     }
                 }
             }
 
-            // Update head_list
+            // Update head_list, reusing the allocation of the old one
             self.min = ptr::null_mut();
-            self.head_list.clear();
+            self.head_list = head_list;
+            self.linked_prefix = 0;
 
             for &n in &self.root_list {
                 if !n.is_null() {
@@ -252,6 +1253,94 @@ impl<T: PartialOrd> FibHeap<T> {
         }
     }
 
+    /// `peek_mut` returns a guard which allows changing the minimum in place,
+    /// or `None` if the heap is empty. If the value was borrowed mutably, the
+    /// guard restores the heap order when it is dropped, which costs about as
+    /// much as an [`extract_min`](FibHeap::extract_min) but does not free or
+    /// allocate a node. A [`NodeHandle`] of the minimum stays valid.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap: FibHeap<_> = [5, 3, 8].into_iter().collect();
+    /// *feap.peek_mut().unwrap() = 9;
+    /// assert_eq!(feap.get_min(), Some(&5));
+    /// ```
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T>> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(PeekMut { heap: self, changed: false })
+    }
+
+    /// `push_pop` inserts `val` and then extracts the minimum, in one step.
+    /// If `val` is not greater than the minimum, it is returned right away
+    /// without touching the heap. Otherwise it takes the place of the minimum
+    /// as in [`replace_min`](FibHeap::replace_min), so no node is allocated
+    /// either way.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap: FibHeap<_> = [5, 3, 8].into_iter().collect();
+    /// assert_eq!(feap.push_pop(1), 1);
+    /// assert_eq!(feap.push_pop(6), 3);
+    /// assert_eq!(feap.get_min(), Some(&5));
+    /// ```
+    pub fn push_pop(&mut self, val: T) -> T {
+        match self.get_min() {
+            Some(min) if *min < val => self.replace_min(val).expect("the heap is not empty"),
+            _ => val,
+        }
+    }
+
+    /// `replace_min` extracts the minimum and inserts `val` in its place,
+    /// reusing the node of the minimum. If the heap is empty, `val` is just
+    /// inserted and `None` is returned. A [`NodeHandle`] of the old minimum
+    /// becomes invalid.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap: FibHeap<_> = [5, 3, 8].into_iter().collect();
+    /// assert_eq!(feap.replace_min(1), Some(3));
+    /// assert_eq!(feap.get_min(), Some(&1));
+    /// assert_eq!(feap.len(), 3);
+    /// ```
+    pub fn replace_min(&mut self, val: T) -> Option<T> {
+        if self.units > 0 {
+            core::mem::forget(val);
+            return Some(unsafe { take_unit() });
+        }
+        if self.min.is_null() {
+            self.insert(val);
+            return None;
+        }
+        let min = self.min;
+        self.release_slot(min);
+        let old = unsafe { core::mem::replace(&mut (*min).val, val) };
+        self.restore_min();
+        Some(old)
+    }
+
+    /// `restore_min` restores the heap order after the value of the minimum
+    /// changed. Its children become roots and all roots are linked again,
+    /// which finds the new minimum.
+    fn restore_min(&mut self) {
+        unsafe {
+            let min = self.min;
+            for c in core::mem::take(&mut (*min).children) {
+                (*c).parent = ptr::null_mut();
+                (*c).marked = false;
+                self.head_list.push(c);
+            }
+            (*min).degree = 0;
+            self.reweigh(min);
+            self.consolidate(true);
+            self.check_invariants();
+        }
+    }
+
     /// `extract_min` returns the value of the current minimum. This deallocates
     /// the current minimum node, causing a consolidation of the tree.
     /// 
@@ -268,32 +1357,399 @@ impl<T: PartialOrd> FibHeap<T> {
     /// assert_eq!(feap.extract_min(), None);
     /// ```
     pub fn extract_min(&mut self) -> Option<T> {
+        if let Some(unit) = self.pop_unit() {
+            self.check_invariants();
+            return Some(unit);
+        }
         unsafe {
             if self.min.is_null() {
                 return None;
             }
 
             let ret = self.min;
+            self.len -= 1;
+            self.release_slot(ret);
 
             self.consolidate(false);
+            self.check_invariants();
+
+            Some(take_node(ret))
+        }
+    }
+
+    /// `extract_min_into` extracts up to `k` minimums in ascending order and
+    /// appends them to `out`, returning how many were appended. Reusing the
+    /// same `out` with enough capacity, no buffer is allocated per batch and
+    /// only the linking of the extractions themselves may allocate.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// for i in [5, 3, 8, 1] {
+    ///     feap.insert(i);
+    /// }
+    ///
+    /// let mut batch = Vec::with_capacity(3);
+    /// assert_eq!(feap.extract_min_into(&mut batch, 3), 3);
+    /// assert_eq!(batch, [1, 3, 5]);
+    ///
+    /// batch.clear();
+    /// assert_eq!(feap.extract_min_into(&mut batch, 3), 1);
+    /// assert_eq!(batch, [8]);
+    /// ```
+    pub fn extract_min_into(&mut self, out: &mut Vec<T>, k: usize) -> usize {
+        let mut n = 0;
+        while n < k {
+            match self.extract_min() {
+                Some(v) => out.push(v),
+                None => break,
+            }
+            n += 1;
+        }
+        n
+    }
+
+    /// `extract_min_n` extracts up to `k` minimums in ascending order. Unlike
+    /// `k` calls to [`extract_min`](FibHeap::extract_min), the trees are
+    /// mostly linked once at the end: the children of every extracted minimum
+    /// just become roots and the next minimum is found by scanning the roots.
+    /// Only while there are more roots than the [`CONSOLIDATION_THRESHOLD`]
+    /// they are linked first, so every scan stays short. This pays off for
+    /// batches which are small compared to the heap.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap: FibHeap<_> = [5, 3, 8, 1].into_iter().collect();
+    /// assert_eq!(feap.extract_min_n(3), [1, 3, 5]);
+    /// assert_eq!(feap.extract_min_n(3), [8]);
+    /// ```
+    pub fn extract_min_n(&mut self, k: usize) -> Vec<T> {
+        let mut out = Vec::with_capacity(k.min(self.len));
+        while out.len() < k {
+            match self.pop_unit() {
+                Some(unit) => out.push(unit),
+                None => break,
+            }
+        }
+        if self.min.is_null() || out.len() == k {
+            return out;
+        }
+
+        self.linked_prefix = 0;
+        self.join_roots();
+        unsafe {
+            while out.len() < k && !self.min.is_null() {
+                if self.head_list.len() > CONSOLIDATION_THRESHOLD {
+                    self.consolidate(true);
+                }
+                let min = self.min;
+                let pos = self.head_list.iter().position(|&n| n == min)
+                    .expect("the minimum is a root");
+                self.head_list.swap_remove(pos);
+                for c in core::mem::take(&mut (*min).children) {
+                    (*c).parent = ptr::null_mut();
+                    (*c).marked = false;
+                    self.head_list.push(c);
+                }
+                self.len -= 1;
+                self.release_slot(min);
+                out.push(take_node(min));
+
+                self.min = ptr::null_mut();
+                for &n in &self.head_list {
+                    if self.min.is_null() || (*n).val < (*self.min).val {
+                        self.min = n;
+                    }
+                }
+            }
+        }
+
+        self.consolidate(true);
+        self.check_invariants();
+        out
+    }
+
+    /// `into_sorted_vec` consumes the heap and returns all values in ascending
+    /// order. The vector is allocated once with the exact length.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let feap: FibHeap<_> = [5, 3, 8, 1].into_iter().collect();
+    /// assert_eq!(feap.into_sorted_vec(), [1, 3, 5, 8]);
+    /// ```
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        let mut sorted = Vec::with_capacity(self.len);
+        self.extract_min_into(&mut sorted, usize::MAX);
+        sorted
+    }
+
+    /// `iter` returns an iterator over references to all values in no
+    /// particular order, without changing the heap.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let feap: FibHeap<_> = [5, 3, 8, 1].into_iter().collect();
+    /// assert_eq!(feap.iter().filter(|&&v| v > 2).count(), 3);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            stack: self.root_lists().map(<[_]>::iter).collect(),
+            units: self.units,
+            remaining: self.len,
+        }
+    }
+
+    /// `drain` removes all values and returns an iterator over them in no
+    /// particular order. Unlike repeated calls to
+    /// [`extract_min`](FibHeap::extract_min) it does not link any trees. The
+    /// heap is empty right away and keeps its buffers for reuse, values which
+    /// are not consumed from the iterator are dropped with it. All
+    /// [`NodeHandle`]s become invalid.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap: FibHeap<_> = [5, 3, 8, 1].into_iter().collect();
+    /// let sum: i32 = feap.drain().sum();
+    /// assert_eq!(sum, 17);
+    /// assert!(feap.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        self.join_roots();
+        let pending = core::mem::take(&mut self.head_list);
+        let units = core::mem::take(&mut self.units);
+        let remaining = self.len;
+        self.min = ptr::null_mut();
+        self.linked_prefix = 0;
+        self.len = 0;
+        self.release_slots();
+        Drain { heap: self, pending, units, remaining }
+    }
+
+    /// `drain_sorted` returns an iterator which removes the values in
+    /// ascending order like [`into_iter_sorted`](FibHeap::into_iter_sorted),
+    /// but keeps the heap and its buffers for reuse. The values which are not
+    /// consumed from the iterator are dropped with it, so the heap is empty
+    /// afterwards.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap: FibHeap<_> = [5, 3, 8, 1].into_iter().collect();
+    /// let smallest: Vec<_> = feap.drain_sorted().take(2).collect();
+    /// assert_eq!(smallest, [1, 3]);
+    /// assert!(feap.is_empty());
+    /// ```
+    pub fn drain_sorted(&mut self) -> DrainSorted<'_, T> {
+        DrainSorted { heap: self }
+    }
+
+    /// `drain_while` returns an iterator which extracts the minimums in
+    /// ascending order as long as `pred` holds for them. It stops at the first
+    /// minimum for which `pred` fails, which stays in the heap like all values
+    /// which are not consumed from the iterator.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut deadlines: FibHeap<_> = [5, 3, 8, 1].into_iter().collect();
+    /// let now = 4;
+    /// let due: Vec<_> = deadlines.drain_while(|&d| d <= now).collect();
+    /// assert_eq!(due, [1, 3]);
+    /// assert_eq!(deadlines.get_min(), Some(&5));
+    /// ```
+    pub fn drain_while<F: FnMut(&T) -> bool>(&mut self, pred: F) -> DrainWhile<'_, T, F> {
+        DrainWhile { heap: self, pred, done: false }
+    }
+
+    /// `into_iter_sorted` consumes the heap and returns an iterator which
+    /// yields the values in ascending order. Every value is only extracted
+    /// when the iterator is advanced, so stopping after the first `k` values
+    /// does not pay for sorting the rest.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let feap: FibHeap<_> = [5, 3, 8, 1].into_iter().collect();
+    /// let smallest: Vec<_> = feap.into_iter_sorted().take(2).collect();
+    /// assert_eq!(smallest, [1, 3]);
+    /// ```
+    pub fn into_iter_sorted(self) -> IntoIterSorted<T> {
+        IntoIterSorted { heap: self }
+    }
+
+    /// `extract_min_budgeted` is [`extract_min`](FibHeap::extract_min) for
+    /// callers which must not be blocked for long, like tasks of a
+    /// cooperative runtime. Linking the roots after many inserts or cuts takes
+    /// time linear in their number, so at most `max_work` roots are linked
+    /// per call. If that is not enough, the progress is kept and
+    /// [`Poll::Pending`] is returned, and the call has to be repeated to get
+    /// the minimum. A single root can take a chain of links, which is bounded
+    /// by the maximum degree.
+    ///
+    /// The heap stays fully usable in between: the minimum is only removed
+    /// by the call which returns it, and other operations just discard the
+    /// parts of the progress they invalidate. With a `max_work` of at least
+    /// one, repeated calls always finish.
+    ///
+    /// ```rust
+    /// use core::task::Poll;
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// feap.set_insert_consolidation(false);
+    /// for i in 0..1000 {
+    ///     feap.insert(i);
+    /// }
+    ///
+    /// let mut polls = 1;
+    /// let min = loop {
+    ///     match feap.extract_min_budgeted(64) {
+    ///         Poll::Ready(min) => break min,
+    ///         Poll::Pending => polls += 1,
+    ///     }
+    ///     assert_eq!(feap.get_min(), Some(&0));
+    /// };
+    /// assert_eq!(min, Some(0));
+    /// assert!(polls > 10);
+    /// ```
+    pub fn extract_min_budgeted(&mut self, max_work: usize) -> Poll<Option<T>> {
+        if self.units > 0 || self.min.is_null() {
+            return Poll::Ready(self.extract_min());
+        }
 
-            Some(Box::from_raw(ret).val)
+        self.join_roots();
+        self.root_list.clear();
+        self.root_list.resize(MAX_DEGREE, ptr::null_mut());
+        let mut head_list = core::mem::take(&mut self.scratch_list);
+        core::mem::swap(&mut head_list, &mut self.head_list);
+        let mut work = 0;
+        let mut paused_at = None;
+        for (i, &c) in head_list.iter().enumerate() {
+            if c == self.min {
+                continue;
+            }
+            // Roots linked by earlier calls are not counted against the budget,
+            // there are at most MAX_DEGREE of them.
+            if i >= self.linked_prefix {
+                if work >= max_work {
+                    paused_at = Some(i);
+                    break;
+                }
+                work += 1;
+            }
+            let mut tmp = insert_root_list(c, &mut self.root_list, self.tie_break);
+            while !tmp.is_null() {
+                tmp = insert_root_list(tmp, &mut self.root_list, self.tie_break);
+            }
+        }
+
+        // The min goes first and was never linked, followed by the linked roots
+        // and the ones still to link.
+        let rest = paused_at.map_or(&[][..], |i| &head_list[i..]);
+        self.head_list.push(self.min);
+        for slot in &mut self.root_list {
+            if !slot.is_null() {
+                self.head_list.push(core::mem::replace(slot, ptr::null_mut()));
+            }
+        }
+        self.linked_prefix = self.head_list.len();
+        self.head_list.extend(rest.iter().filter(|&&c| c != self.min));
+        head_list.clear();
+        self.scratch_list = head_list;
+        self.check_invariants();
+
+        if paused_at.is_some() {
+            Poll::Pending
+        } else {
+            // At most MAX_DEGREE roots and the children of the min are left.
+            Poll::Ready(self.extract_min())
         }
     }
 
-    /// `find_elem` is a helper function, which traverses a tree, trying to find
-    /// a node with a given value.
-    fn find_elem(&self, cur_node: Link<T>, val: &T) -> Option<Link<T>> {
+    /// `validate` checks the structural invariants of the heap and panics with a
+    /// description of the first violation it finds. Every root must be
+    /// parentless, every child must point back to its parent and not be
+    /// smaller than it, the [`degree`](Node::degree) of a node must match its
+    /// number of children, [`min`](FibHeap::min) must be a root holding the
+    /// smallest value among all roots and [`len`](FibHeap::len) must match the
+    /// number of nodes. If the heap has a [`set_weight`](FibHeap::set_weight)
+    /// function, the weight sum of every node must match its subtree.
+    ///
+    /// This is meant for tests and fuzzing and walks the whole heap.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// for i in 0..10 {
+    ///     feap.insert(i);
+    /// }
+    /// feap.extract_min();
+    /// feap.validate();
+    /// ```
+    pub fn validate(&self) {
         unsafe {
-            if (*cur_node).val.eq(val) {
-                return Some(cur_node);
+            assert_eq!(self.min.is_null(), self.root_lists().all(<[_]>::is_empty),
+                "min is null if and only if there are no roots");
+            for &r in self.root_lists().flatten() {
+                assert!((*r).parent.is_null(), "root has a parent");
+                assert_ne!((*r).val.partial_cmp(&(*self.min).val), Some(Ordering::Less),
+                    "root is smaller than min");
+                self.validate_tree(r);
+            }
+            if !self.min.is_null() {
+                assert!(self.root_lists().any(|l| l.contains(&self.min)), "min is not a root");
             }
-            for &c in &(*cur_node).children {
-                if let Some(r) = self.find_elem(c, val) {
-                    return Some(r);
+            let mut nodes = 0;
+            self.find_node(|_| {
+                nodes += 1;
+                false
+            });
+            assert!(Self::ZERO_SIZED || self.units == 0, "values are counted without a node");
+            assert_eq!(nodes + self.units, self.len, "len does not match the number of nodes");
+            for (i, entry) in self.slots.iter().enumerate() {
+                if !entry.node.is_null() {
+                    assert_eq!((*entry.node).slot as usize, i, "node does not own its slot");
                 }
             }
-            None
+        }
+    }
+
+    /// `check_invariants` runs [`validate`](FibHeap::validate) at the end of
+    /// every mutating operation if the `paranoid` feature is enabled, so a
+    /// corruption is caught by the operation that caused it. The crate's own
+    /// unit tests always run with these checks, except under Miri, where
+    /// walking the whole heap after every operation makes them too slow.
+    #[inline]
+    fn check_invariants(&self) {
+        if cfg!(any(all(test, not(miri)), feature = "paranoid")) {
+            self.validate();
+        }
+    }
+
+    /// The recursive part of [`validate`](FibHeap::validate) which checks a
+    /// node and all of its descendants.
+    fn validate_tree(&self, node: Link<T>) {
+        unsafe {
+            assert_eq!((*node).degree as usize, (*node).children.len(),
+                "degree does not match the number of children");
+            for &c in &(*node).children {
+                assert_eq!((*c).parent, node, "child does not point to its parent");
+                assert_ne!((*c).val.partial_cmp(&(*node).val), Some(Ordering::Less),
+                    "child is smaller than its parent");
+                self.validate_tree(c);
+            }
+            if let Some(weigh) = self.weigh {
+                let children: f64 = (*node).children.iter().map(|&c| (*c).weight).sum();
+                let weight = own_weight(weigh, &(*node).val) + children;
+                assert!((weight - (*node).weight).abs() <= weight * 1e-9,
+                    "weight does not match the weights of the subtree");
+            }
         }
     }
 
@@ -305,11 +1761,15 @@ impl<T: PartialOrd> FibHeap<T> {
             (*node).marked = false;
             if !(*node).parent.is_null() {
                 let parent = (*node).parent;
+                (*node).parent = ptr::null_mut();
                 self.insert_node(node);
                 let idx = (*parent).children.iter()
-                    .position(|&v| (*v).val == (*node).val)
+                    .position(|&v| v == node)
                     .unwrap();
                 (*parent).children.remove(idx);
+                (*parent).degree -= 1;
+                shrink_children(parent);
+                self.reweigh_path(parent);
                 if !(*parent).marked {
                     (*parent).marked = true;
                 } else {
@@ -319,9 +1779,38 @@ impl<T: PartialOrd> FibHeap<T> {
         }
     }
 
+    /// `reweigh` recomputes the [`weight`](Node::weight) of `node` from its
+    /// value and the weights of its children, if the heap weighs its values.
+    fn reweigh(&self, node: Link<T>) {
+        if let Some(weigh) = self.weigh {
+            unsafe {
+                let children: f64 = (*node).children.iter().map(|&c| (*c).weight).sum();
+                (*node).weight = own_weight(weigh, &(*node).val) + children;
+            }
+        }
+    }
+
+    /// `reweigh_path` [`reweigh`](FibHeap::reweigh)s `node` and all of its
+    /// ancestors up to the root.
+    fn reweigh_path(&self, mut node: Link<T>) {
+        if self.weigh.is_none() {
+            return;
+        }
+        while !node.is_null() {
+            self.reweigh(node);
+            node = unsafe { (*node).parent };
+        }
+    }
+
     /// `decrease_key` looks for a node with the value `old_val` and changes it
     /// to `new_val`. If the new value would invalidate the heap property, the
-    /// node will be cut out.
+    /// node will be cut out. Nothing is changed if no value matches or if
+    /// `new_val` is greater than the old value, see
+    /// [`try_decrease_key`](FibHeap::try_decrease_key) to tell these apart.
+    ///
+    /// Like the lookups of a `HashMap`, `old_val` can be any borrowed form of
+    /// the stored values, so no owned value has to be built just to find the
+    /// node.
     /// 
     /// ```rust
     /// use feap::FibHeap;
@@ -331,34 +1820,931 @@ impl<T: PartialOrd> FibHeap<T> {
     /// feap.insert(10);
     /// assert_eq!(feap.get_min(), Some(&5));
     /// 
-    /// feap.decrease_key(10, 3);
+    /// feap.decrease_key(&10, 3);
     /// assert_eq!(feap.get_min(), Some(&3));
+    ///
+    /// let mut names = FibHeap::new();
+    /// names.insert(String::from("bob"));
+    /// names.insert(String::from("carol"));
+    /// names.decrease_key("carol", String::from("alice"));
+    /// assert_eq!(names.get_min().map(String::as_str), Some("alice"));
     /// ```
-    pub fn decrease_key(&mut self, old_val: T, new_val: T) {
-        unsafe {
-            let mut cur_node = None;
-            for &t in &self.head_list {
-                cur_node = self.find_elem(t, &old_val);
-                if cur_node.is_some() { break; }
-            }
-
-            if let Some(cur_node) = cur_node {
-                let parent = (*cur_node).parent;
-                if !parent.is_null() && (*parent).val >= new_val {
-                    self.cut_out(cur_node);
-                } else if parent.is_null() && (*self.min).val > new_val {
-                    self.min = cur_node;
-                }
-                (*cur_node).val = new_val;
-            }
-        }
+    pub fn decrease_key<Q>(&mut self, old_val: &Q, new_val: T)
+        where
+            T: Borrow<Q>,
+            Q: PartialEq + ?Sized {
+        let _ = self.try_decrease_key(old_val, new_val);
     }
-}
 
-/// `insert_root_list` is a helper, that inserts a node into a root_list or
-/// merges them if there already is a node with the same degree in the 
-/// root_list.
-fn insert_root_list<T>(link: Link<T>, root_list: &mut [Link<T>]) -> Link<T> 
+    /// `try_decrease_key` is [`decrease_key`](FibHeap::decrease_key), but
+    /// reports why nothing was changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FeapError::NotFound`] if no value matches `old_val` and
+    /// [`FeapError::NewKeyGreater`] if `new_val` is greater than the value it
+    /// would replace.
+    ///
+    /// ```rust
+    /// use feap::{FeapError, FibHeap};
+    ///
+    /// let mut feap = FibHeap::new();
+    /// feap.insert(5);
+    ///
+    /// assert_eq!(feap.try_decrease_key(&7, 1), Err(FeapError::NotFound));
+    /// assert_eq!(feap.try_decrease_key(&5, 6), Err(FeapError::NewKeyGreater));
+    /// assert_eq!(feap.try_decrease_key(&5, 4), Ok(()));
+    /// assert_eq!(feap.get_min(), Some(&4));
+    /// ```
+    pub fn try_decrease_key<Q>(&mut self, old_val: &Q, new_val: T) -> Result<(), FeapError>
+        where
+            T: Borrow<Q>,
+            Q: PartialEq + ?Sized {
+        self.decrease_by(|v| v.borrow() == old_val, new_val)
+    }
+
+    /// `decrease_key_by` changes the first value found for which `f` returns
+    /// true to `new_val`, like [`try_decrease_key`](FibHeap::try_decrease_key).
+    /// This finds values by a part of them, for example the
+    /// [`primary`](MultiKey::primary) key of a [`MultiKey`], where a
+    /// [`Borrow`] of that part would not agree with the order of the whole
+    /// value.
+    ///
+    /// # Errors
+    ///
+    /// The same as for [`try_decrease_key`](FibHeap::try_decrease_key).
+    ///
+    /// ```rust
+    /// use feap::{FibHeap, Keyed};
+    ///
+    /// let mut jobs = FibHeap::new();
+    /// jobs.insert(Keyed::new(5, "backup"));
+    /// jobs.insert(Keyed::new(9, "alert"));
+    ///
+    /// jobs.decrease_key_by(|j| j.value == "alert", Keyed::new(1, "alert")).unwrap();
+    /// assert_eq!(jobs.get_min().map(|j| j.value), Some("alert"));
+    /// ```
+    pub fn decrease_key_by(
+        &mut self,
+        f: impl FnMut(&T) -> bool,
+        new_val: T,
+    ) -> Result<(), FeapError> {
+        self.decrease_by(f, new_val)
+    }
+
+    /// `decrease_by` is the part of [`decrease_key_by`](FibHeap::decrease_key_by)
+    /// and [`try_decrease_key`](FibHeap::try_decrease_key) which looks at the
+    /// [`units`](FibHeap::units) before the nodes. A unit is replaced by the
+    /// new value, which is counted in its place.
+    fn decrease_by(
+        &mut self,
+        mut f: impl FnMut(&T) -> bool,
+        new_val: T,
+    ) -> Result<(), FeapError> {
+        if self.units > 0 && f(unsafe { unit() }) {
+            if !matches!(new_val.partial_cmp(unsafe { unit() }),
+                    Some(Ordering::Less | Ordering::Equal)) {
+                return Err(FeapError::NewKeyGreater);
+            }
+            drop(unsafe { take_unit::<T>() });
+            core::mem::forget(new_val);
+            return Ok(());
+        }
+        let node = self.find_node(f).ok_or(FeapError::NotFound)?;
+        let ret = self.decrease_node(node, new_val);
+        self.check_invariants();
+        ret
+    }
+
+    /// `decrease_key_by_handle` changes the value of `handle` to `new_val`
+    /// like [`decrease_key`](FibHeap::decrease_key), but finds the node
+    /// directly instead of searching for it, which makes it amortized O(1).
+    /// Invalid handles and values greater than the old one are ignored.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// feap.insert(5);
+    /// let ten = feap.insert_with_handle(10);
+    ///
+    /// feap.decrease_key_by_handle(ten, 3);
+    /// assert_eq!(feap.get_min(), Some(&3));
+    /// assert_eq!(feap.get(ten), Some(&3));
+    /// ```
+    pub fn decrease_key_by_handle(&mut self, handle: NodeHandle, new_val: T) {
+        if let Some(node) = self.handle_node(handle) {
+            let _ = self.decrease_node(node, new_val);
+        }
+        self.check_invariants();
+    }
+
+    /// `delete` removes the value of `handle` from the heap and returns it, or
+    /// `None` if the handle is no longer valid. The node is cut out of its
+    /// tree, its children become roots and the heap is consolidated as after
+    /// an [`extract_min`](FibHeap::extract_min).
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// let cancelled = feap.insert_with_handle(2);
+    /// feap.insert(1);
+    /// feap.insert(3);
+    ///
+    /// assert_eq!(feap.delete(cancelled), Some(2));
+    /// assert_eq!(feap.delete(cancelled), None);
+    /// assert_eq!(feap.extract_min(), Some(1));
+    /// assert_eq!(feap.extract_min(), Some(3));
+    /// ```
+    pub fn delete(&mut self, handle: NodeHandle) -> Option<T> {
+        let node = self.handle_node(handle)?;
+        Some(self.remove_node(node))
+    }
+
+    /// `decrease_node` stores `new_val` in `node` and restores the heap
+    /// property, by cutting the node out if it is now smaller than its parent
+    /// or making it the minimum if it is a root. A `new_val` which is not
+    /// smaller than or equal to the old value is rejected, as the children
+    /// of the node could end up smaller than it.
+    fn decrease_node(&mut self, node: Link<T>, new_val: T) -> Result<(), FeapError> {
+        unsafe {
+            if !matches!(new_val.partial_cmp(&(*node).val),
+                    Some(Ordering::Less | Ordering::Equal)) {
+                return Err(FeapError::NewKeyGreater);
+            }
+            (*node).val = new_val;
+            self.reweigh_path(node);
+            let parent = (*node).parent;
+            if !parent.is_null() && (*parent).val > (*node).val {
+                self.cut_out(node);
+            } else if parent.is_null() && (*node).val < (*self.min).val {
+                self.min = node;
+            }
+        }
+        Ok(())
+    }
+
+    /// `contains` returns whether any value in the heap equals `val`. Every
+    /// tree is searched down to its leaves, so this is O(n). Like in
+    /// [`decrease_key`](FibHeap::decrease_key), `val` can be any borrowed
+    /// form of the stored values.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// for i in 0..10 {
+    ///     feap.insert(i);
+    /// }
+    /// feap.extract_min();
+    ///
+    /// assert!(feap.contains(&9));
+    /// assert!(!feap.contains(&0));
+    /// ```
+    pub fn contains<Q>(&self, val: &Q) -> bool
+        where
+            T: Borrow<Q>,
+            Q: PartialEq + ?Sized {
+        let unit = self.units > 0 && unsafe { unit::<T>() }.borrow() == val;
+        unit || self.find_node(|v| v.borrow() == val).is_some()
+    }
+
+    /// `count` returns how many values in the heap equal `val`. Like
+    /// [`contains`](FibHeap::contains) it walks the whole heap.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// for i in [1, 2, 2, 3, 2] {
+    ///     feap.insert(i);
+    /// }
+    /// assert_eq!(feap.count(&2), 3);
+    /// assert_eq!(feap.count(&4), 0);
+    /// ```
+    pub fn count<Q>(&self, val: &Q) -> usize
+        where
+            T: Borrow<Q>,
+            Q: PartialEq + ?Sized {
+        let mut count = 0;
+        if self.units > 0 && unsafe { unit::<T>() }.borrow() == val {
+            count = self.units;
+        }
+        self.find_node(|v| {
+            if v.borrow() == val {
+                count += 1;
+            }
+            false
+        });
+        count
+    }
+
+    /// `remove` takes the first value found which equals `val` out of the heap
+    /// and returns it, or `None` if there is no such value. The search walks
+    /// the whole heap, but removing is as cheap as with
+    /// [`delete`](FibHeap::delete). Like in
+    /// [`decrease_key`](FibHeap::decrease_key), `val` can be any borrowed
+    /// form of the stored values.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut jobs = FibHeap::new();
+    /// jobs.insert(String::from("backup"));
+    /// jobs.insert(String::from("alert"));
+    ///
+    /// assert_eq!(jobs.remove("backup").as_deref(), Some("backup"));
+    /// assert_eq!(jobs.remove("backup"), None);
+    /// assert_eq!(jobs.len(), 1);
+    /// ```
+    pub fn remove<Q>(&mut self, val: &Q) -> Option<T>
+        where
+            T: Borrow<Q>,
+            Q: PartialEq + ?Sized {
+        self.remove_by(|v| v.borrow() == val)
+    }
+
+    /// `remove_by` takes the first value found for which `f` returns true out
+    /// of the heap and returns it, like [`remove`](FibHeap::remove).
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap: FibHeap<_> = [(1, 'a'), (2, 'b')].into_iter().collect();
+    /// assert_eq!(feap.remove_by(|&(_, c)| c == 'b'), Some((2, 'b')));
+    /// assert_eq!(feap.len(), 1);
+    /// ```
+    pub fn remove_by(&mut self, mut f: impl FnMut(&T) -> bool) -> Option<T> {
+        if self.units > 0 && f(unsafe { unit() }) {
+            return self.pop_unit();
+        }
+        let node = self.find_node(f)?;
+        Some(self.remove_node(node))
+    }
+
+    /// `remove_node` takes an arbitrary node out of the heap and returns its
+    /// value. The node is cut out of its tree if needed and then removed as if
+    /// it were the minimum, so its children become roots and the heap is
+    /// consolidated.
+    fn remove_node(&mut self, node: Link<T>) -> T {
+        unsafe {
+            if !(*node).parent.is_null() {
+                self.cut_out(node);
+            }
+            self.min = node;
+            self.len -= 1;
+            self.release_slot(node);
+            self.consolidate(false);
+            self.check_invariants();
+            take_node(node)
+        }
+    }
+
+    /// `pop_weighted_random` removes a random element, picking each element
+    /// with a probability proportional to its weight as given by the function
+    /// set with [`set_weight`](FibHeap::set_weight). `rng` has to return
+    /// uniformly distributed numbers in `0.0..1.0`. If no weight function is
+    /// set, no element has a positive weight or the weights do not sum up to
+    /// a finite number, `None` is returned.
+    ///
+    /// The pick descends from the roots by the weight sums of the subtrees,
+    /// so it looks at the roots and at the children of each node on the path
+    /// to the picked one instead of at the whole heap. Removing it costs the
+    /// same as a [`delete`](FibHeap::delete).
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// feap.insert(1);
+    /// feap.insert(2);
+    /// feap.insert(3);
+    ///
+    /// // Only odd values have a weight, and 0.9 falls into the range of 3.
+    /// feap.set_weight(Some(|v| (v % 2) as f64));
+    /// assert_eq!(feap.pop_weighted_random(|| 0.9), Some(3));
+    /// assert_eq!(feap.pop_weighted_random(|| 0.9), Some(1));
+    /// assert_eq!(feap.pop_weighted_random(|| 0.9), None);
+    /// assert_eq!(feap.get_min(), Some(&2));
+    /// ```
+    pub fn pop_weighted_random(&mut self, mut rng: impl FnMut() -> f64) -> Option<T> {
+        let weigh = self.weigh?;
+        self.join_roots();
+        let units = if self.units > 0 {
+            self.units as f64 * own_weight(weigh, unsafe { unit() })
+        } else {
+            0.0
+        };
+        let roots: f64 = self.head_list.iter().map(|&r| unsafe { (*r).weight }).sum();
+        let total = units + roots;
+        if total <= 0.0 || !total.is_finite() {
+            return None;
+        }
+
+        let mut target = rng() * total;
+        if target < units {
+            return self.pop_unit();
+        }
+        target -= units;
+        let mut node = pick_weighted(&self.head_list, &mut target)?;
+        unsafe {
+            loop {
+                let own = own_weight(weigh, &(*node).val);
+                if target < own {
+                    break;
+                }
+                target -= own;
+                match pick_weighted(&(*node).children, &mut target) {
+                    Some(c) => node = c,
+                    None => break,
+                }
+            }
+        }
+        Some(self.remove_node(node))
+    }
+
+    /// `split_by` moves every value for which `f` returns true into a new
+    /// heap, which is returned. The nodes are moved over as they are, in a
+    /// single walk over the heap followed by one consolidation of each heap.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// for i in 0..10 {
+    ///     feap.insert(i);
+    /// }
+    ///
+    /// let mut odd = feap.split_by(|v| v % 2 == 1);
+    /// assert_eq!(feap.extract_min(), Some(0));
+    /// assert_eq!(odd.extract_min(), Some(1));
+    /// assert_eq!(odd.extract_min(), Some(3));
+    /// ```
+    pub fn split_by(&mut self, mut f: impl FnMut(&T) -> bool) -> Self {
+        let mut other = Self::new();
+        other.tie_break = self.tie_break;
+        other.consolidate_on_insert = self.consolidate_on_insert;
+        other.wipe = self.wipe;
+        other.weigh = self.weigh;
+
+        let moved = (0..self.units).filter(|_| f(unsafe { unit() })).count();
+        self.units -= moved;
+        other.units = moved;
+        other.len = moved;
+
+        self.min = ptr::null_mut();
+        self.linked_prefix = 0;
+        self.len = self.units;
+        self.join_roots();
+        let mut head_list = core::mem::take(&mut self.head_list);
+        for t in head_list.drain(..) {
+            unlink_tree(t, &mut |node| unsafe {
+                self.reweigh(node);
+                if f(&(*node).val) {
+                    self.release_slot(node);
+                    other.insert_node(node);
+                    other.len += 1;
+                } else {
+                    self.insert_node(node);
+                    self.len += 1;
+                }
+            });
+        }
+        // Keep the allocation of the old head_list if it is the larger one.
+        if head_list.capacity() > self.head_list.capacity() {
+            head_list.append(&mut self.head_list);
+            self.head_list = head_list;
+        }
+
+        self.consolidate(true);
+        other.consolidate(true);
+        self.check_invariants();
+        other.check_invariants();
+        other
+    }
+
+    /// `retain` removes every value for which `f` returns false. All trees are
+    /// taken apart in a single walk and the remaining nodes are consolidated
+    /// once, so this takes O(n) time no matter how many values are removed.
+    /// The [`NodeHandle`]s of the remaining values stay valid.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap: FibHeap<_> = (0..10).collect();
+    /// feap.retain(|v| v % 3 == 0);
+    /// assert_eq!(feap.into_sorted_vec(), [0, 3, 6, 9]);
+    /// ```
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        let kept = (0..self.units).filter(|_| f(unsafe { unit() })).count();
+        while self.units > kept {
+            self.units -= 1;
+            drop(unsafe { take_unit::<T>() });
+        }
+
+        self.min = ptr::null_mut();
+        self.linked_prefix = 0;
+        self.len = self.units;
+        self.join_roots();
+        let mut head_list = core::mem::take(&mut self.head_list);
+        for t in head_list.drain(..) {
+            unlink_tree(t, &mut |node| unsafe {
+                if f(&(*node).val) {
+                    self.reweigh(node);
+                    self.insert_node(node);
+                    self.len += 1;
+                } else {
+                    self.release_slot(node);
+                    free_node(node, self.wipe);
+                }
+            });
+        }
+        if head_list.capacity() > self.head_list.capacity() {
+            head_list.append(&mut self.head_list);
+            self.head_list = head_list;
+        }
+
+        self.consolidate(true);
+        self.check_invariants();
+    }
+
+    /// `split_by_key` moves every value which is less than `threshold` into a
+    /// new heap, which is returned, and keeps the rest. Since no value of a
+    /// tree is less than its root, the trees are only walked down to the first
+    /// values which are not less than `threshold`, and the subtrees below them
+    /// stay in this heap as they are.
+    ///
+    /// ```rust
+    /// use feap::FibHeap;
+    ///
+    /// let mut deadlines: FibHeap<_> = [5, 3, 8, 1].into_iter().collect();
+    /// let mut soon = deadlines.split_by_key(&4);
+    /// assert_eq!(soon.into_sorted_vec(), [1, 3]);
+    /// assert_eq!(deadlines.into_sorted_vec(), [5, 8]);
+    /// ```
+    pub fn split_by_key(&mut self, threshold: &T) -> Self {
+        let mut other = Self::new();
+        other.tie_break = self.tie_break;
+        other.consolidate_on_insert = self.consolidate_on_insert;
+        other.wipe = self.wipe;
+        other.weigh = self.weigh;
+
+        if self.units > 0 && unsafe { unit::<T>() } < threshold {
+            other.units = core::mem::take(&mut self.units);
+            other.len = other.units;
+            self.len -= other.units;
+        }
+
+        self.min = ptr::null_mut();
+        self.linked_prefix = 0;
+        self.join_roots();
+        let mut head_list = core::mem::take(&mut self.head_list);
+        for t in head_list.drain(..) {
+            split_tree(t, threshold, &mut |node, below| {
+                if below {
+                    self.reweigh(node);
+                    self.release_slot(node);
+                    self.len -= 1;
+                    other.insert_node(node);
+                    other.len += 1;
+                } else {
+                    self.insert_node(node);
+                }
+            });
+        }
+        if head_list.capacity() > self.head_list.capacity() {
+            head_list.append(&mut self.head_list);
+            self.head_list = head_list;
+        }
+
+        self.consolidate(true);
+        other.consolidate(true);
+        self.check_invariants();
+        other.check_invariants();
+        other
+    }
+
+    /// `into_max_heap` turns the heap into one which yields the largest value
+    /// first. The values are moved into new roots in a single pass and
+    /// consolidated at most once, so this is O(n) and no values are copied.
+    /// A [`Reverse`] has the layout of its value, so the nodes are reused as
+    /// well, together with the ones kept by
+    /// [`clear_and_recycle`](FibHeap::clear_and_recycle).
+    ///
+    /// The new heap keeps the [`TieBreak`] and the insert consolidation, and
+    /// wipes its values if this one does, see
+    /// [`set_zeroize`](FibHeap::set_zeroize). The
+    /// [`set_weight`](FibHeap::set_weight) function weighs the values
+    /// themselves and is not kept. All [`NodeHandle`]s become invalid.
+    ///
+    /// ```rust
+    /// use core::cmp::Reverse;
+    /// use feap::FibHeap;
+    ///
+    /// let mut feap = FibHeap::new();
+    /// for i in [4, 1, 3, 2] {
+    ///     feap.insert(i);
+    /// }
+    /// assert_eq!(feap.extract_min(), Some(1));
+    ///
+    /// let mut max = feap.into_max_heap();
+    /// assert_eq!(max.extract_min(), Some(Reverse(4)));
+    ///
+    /// let mut min = max.into_min_heap();
+    /// assert_eq!(min.extract_min(), Some(2));
+    /// ```
+    pub fn into_max_heap(self) -> FibHeap<Reverse<T>> {
+        // A Reverse is repr(transparent), so a function wiping the value
+        // wipes the Reverse around it.
+        let wipe = self.wipe.map(|w| unsafe {
+            core::mem::transmute::<fn(&mut T), fn(&mut Reverse<T>)>(w)
+        });
+        self.map_into(wipe, Reverse)
+    }
+
+    /// `map_into` moves every value through `f` into a new heap, which keeps
+    /// the [`TieBreak`] and the insert consolidation of this one and wipes its
+    /// values with `wipe`. All values become roots, consolidated at most once
+    /// at the end. If the nodes of both heaps have the same layout, the nodes
+    /// and the [`spare`](FibHeap::spare) ones are moved over instead of
+    /// allocating new ones.
+    fn map_into<U: PartialOrd>(
+        mut self,
+        wipe: Wiper<U>,
+        mut f: impl FnMut(T) -> U,
+    ) -> FibHeap<U> {
+        let mut new = FibHeap::new();
+        new.tie_break = self.tie_break;
+        new.consolidate_on_insert = self.consolidate_on_insert;
+        new.wipe = wipe;
+        new.head_list.reserve(self.len - self.units);
+        let reuse = Layout::new::<Node<U>>() == Layout::new::<Node<T>>()
+            && !FibHeap::<U>::ZERO_SIZED;
+        if reuse {
+            new.spare = core::mem::take(&mut self.spare).into_iter().map(<*mut _>::cast).collect();
+        }
+
+        while let Some(unit) = self.pop_unit() {
+            new.push_value(f(unit));
+        }
+        self.min = ptr::null_mut();
+        self.linked_prefix = 0;
+        self.len = 0;
+        self.join_roots();
+        for t in core::mem::take(&mut self.head_list) {
+            unlink_tree(t, &mut |node| unsafe {
+                if !reuse {
+                    new.push_value(f(take_node(node)));
+                    return;
+                }
+                // The node is a single root now, so only its value and its
+                // empty list of children are left to move.
+                let val = f(ptr::read(ptr::addr_of!((*node).val)));
+                let mut children =
+                    core::mem::ManuallyDrop::new(ptr::read(ptr::addr_of!((*node).children)));
+                let mut moved = Node::new(val);
+                moved.children =
+                    Vec::from_raw_parts(children.as_mut_ptr().cast(), 0, children.capacity());
+                let node = node.cast();
+                ptr::write(node, moved);
+                new.insert_node(node);
+                new.len += 1;
+            });
+        }
+        if new.consolidate_on_insert && new.head_list.len() > CONSOLIDATION_THRESHOLD {
+            new.consolidate(true);
+        }
+        new.check_invariants();
+        new
+    }
+
+    /// `into_values` moves all values out of the heap in no particular order.
+    fn into_values(mut self) -> Vec<T> {
+        let mut values = Vec::with_capacity(self.len);
+        while let Some(unit) = self.pop_unit() {
+            values.push(unit);
+        }
+        self.min = ptr::null_mut();
+        self.linked_prefix = 0;
+        self.len = 0;
+        self.join_roots();
+        for t in core::mem::take(&mut self.head_list) {
+            take_tree(t, &mut |val| values.push(val));
+        }
+        values
+    }
+
+    /// `find_node` walks all trees and returns the first node for which `f`
+    /// returns true.
+    fn find_node(&self, mut f: impl FnMut(&T) -> bool) -> Option<Link<T>> {
+        self.root_lists().flatten().find_map(|&t| find_in_tree(t, &mut f))
+    }
+}
+
+/// `find_in_tree` is the recursive part of [`find_node`](FibHeap::find_node).
+fn find_in_tree<T>(node: Link<T>, f: &mut impl FnMut(&T) -> bool) -> Option<Link<T>> {
+    unsafe {
+        if f(&(*node).val) {
+            return Some(node);
+        }
+        (*node).children.iter().find_map(|&c| find_in_tree(c, f))
+    }
+}
+
+impl<T: PartialOrd> FibHeap<Reverse<T>> {
+    /// `into_min_heap` undoes [`into_max_heap`](FibHeap::into_max_heap), so
+    /// the heap yields the smallest value first again. The nodes and the
+    /// settings carry over the same way.
+    pub fn into_min_heap(self) -> FibHeap<T> {
+        let wipe = self.wipe.map(|w| unsafe {
+            core::mem::transmute::<fn(&mut Reverse<T>), fn(&mut T)>(w)
+        });
+        self.map_into(wipe, |Reverse(v)| v)
+    }
+}
+
+/// `own_weight` returns the weight of `val` given by `weigh`, with weights
+/// which are not positive counted as zero.
+fn own_weight<T>(weigh: fn(&T) -> f64, val: &T) -> f64 {
+    let w = weigh(val);
+    if w > 0.0 { w } else { 0.0 }
+}
+
+/// `weigh_tree` computes the [`weight`](Node::weight) of a node and all of
+/// its descendants from scratch and returns the one of the node.
+fn weigh_tree<T>(node: Link<T>, weigh: fn(&T) -> f64) -> f64 {
+    unsafe {
+        let mut weight = own_weight(weigh, &(*node).val);
+        for &c in &(*node).children {
+            weight += weigh_tree(c, weigh);
+        }
+        (*node).weight = weight;
+        weight
+    }
+}
+
+/// `pick_weighted` returns the node of `nodes` whose range of the summed up
+/// [`weight`](Node::weight)s holds `target`, and makes `target` relative to
+/// the start of that range. Rounding can leave `target` just past the end
+/// of the last range, in which case the last node with a weight is picked.
+fn pick_weighted<T>(nodes: &[Link<T>], target: &mut f64) -> Option<Link<T>> {
+    let mut last = None;
+    for &n in nodes {
+        let w = unsafe { (*n).weight };
+        if w <= 0.0 {
+            continue;
+        }
+        if *target < w {
+            return Some(n);
+        }
+        *target -= w;
+        last = Some(n);
+    }
+    if let Some(n) = last {
+        *target = unsafe { (*n).weight };
+    }
+    last
+}
+
+/// `take_tree` deallocates a node and all of its descendants like
+/// [`free_tree`], but passes their values to `f` instead of dropping them.
+fn take_tree<T>(node: Link<T>, f: &mut impl FnMut(T)) {
+    unsafe {
+        let children = core::mem::take(&mut (*node).children);
+        f(take_node(node));
+        for c in children {
+            take_tree(c, f);
+        }
+    }
+}
+
+/// `unlink_tree` turns a node and all of its descendants into single,
+/// unmarked roots and passes each of them to `f`. The lists of children are
+/// emptied but keep their capacity for the next consolidation.
+fn unlink_tree<T>(node: Link<T>, f: &mut impl FnMut(Link<T>)) {
+    unsafe {
+        for i in 0..(*node).children.len() {
+            unlink_tree((&(*node).children)[i], f);
+        }
+        (*node).children.clear();
+        (*node).parent = ptr::null_mut();
+        (*node).degree = 0;
+        (*node).marked = false;
+        f(node);
+    }
+}
+
+/// `split_tree` passes every node of a tree which is less than `threshold` to
+/// `f` as a single root, together with `true`. The first nodes on each path
+/// which are not less than `threshold` are passed with `false` as the roots of
+/// their whole subtrees.
+fn split_tree<T: PartialOrd>(node: Link<T>, threshold: &T, f: &mut impl FnMut(Link<T>, bool)) {
+    unsafe {
+        (*node).parent = ptr::null_mut();
+        (*node).marked = false;
+        if (*node).val.partial_cmp(threshold) != Some(Ordering::Less) {
+            f(node, false);
+            return;
+        }
+        for i in 0..(*node).children.len() {
+            split_tree((&(*node).children)[i], threshold, f);
+        }
+        (*node).children.clear();
+        (*node).degree = 0;
+        f(node, true);
+    }
+}
+
+/// `free_tree` deallocates a node and all of its descendants.
+fn free_tree<T>(node: Link<T>, wipe: Wiper<T>) {
+    unsafe {
+        for &c in &(*node).children {
+            free_tree(c, wipe);
+        }
+        free_node(node, wipe);
+    }
+}
+
+/// `recycle_tree` drops the values of a node and all of its descendants and
+/// pushes the nodes to `spare` instead of deallocating them. The nodes are
+/// wiped like by [`free_node`].
+fn recycle_tree<T>(node: Link<T>, spare: &mut Vec<Link<T>>, wipe: Wiper<T>) {
+    unsafe {
+        for &c in &(*node).children {
+            recycle_tree(c, spare, wipe);
+        }
+        drop_val(node, wipe);
+        wipe_node(node);
+        spare.push(node);
+    }
+}
+
+/// `take_node` moves the value out of `node` and deallocates it. With the
+/// `zeroize` feature the memory of the value is wiped before it is freed, so
+/// no copy of it is left behind in the allocator.
+///
+/// # Safety
+///
+/// `node` has to be a node allocated by this crate which is not referenced
+/// anymore.
+unsafe fn take_node<T>(node: Link<T>) -> T {
+    let val = ptr::read(ptr::addr_of!((*node).val));
+    release_node(node);
+    val
+}
+
+/// `unit` returns a reference to one of the [`units`](FibHeap::units) of a
+/// heap. A zero-sized value takes no memory, so any aligned pointer which is
+/// not null points to it.
+///
+/// # Safety
+///
+/// `T` has to be zero-sized and the heap has to count at least one unit.
+unsafe fn unit<'a, T>() -> &'a T {
+    debug_assert_eq!(core::mem::size_of::<T>(), 0);
+    ptr::NonNull::dangling().as_ref()
+}
+
+/// `take_unit` moves one of the [`units`](FibHeap::units) of a heap out of
+/// it, see [`unit`].
+///
+/// # Safety
+///
+/// The same as for [`unit`], and the unit has to be uncounted, so it is not
+/// taken twice.
+unsafe fn take_unit<T>() -> T {
+    debug_assert_eq!(core::mem::size_of::<T>(), 0);
+    ptr::read(ptr::NonNull::dangling().as_ptr())
+}
+
+/// `free_node` drops the value of `node` in place like [`drop_val`] and
+/// deallocates it, wiping it first like [`take_node`].
+///
+/// # Safety
+///
+/// The same as for [`take_node`].
+unsafe fn free_node<T>(node: Link<T>, wipe: Wiper<T>) {
+    drop_val(node, wipe);
+    release_node(node);
+}
+
+/// `drop_val` drops the value of `node` in place. If the heap wipes its
+/// values, the value is wiped first, which also clears the memory it owns
+/// like the buffer of a [`Vec`].
+///
+/// # Safety
+///
+/// The same as for [`take_node`].
+unsafe fn drop_val<T>(node: Link<T>, wipe: Wiper<T>) {
+    if let Some(wipe) = wipe {
+        wipe(&mut (*node).val);
+    }
+    ptr::drop_in_place(ptr::addr_of_mut!((*node).val));
+}
+
+/// `release_node` deallocates a node whose value has already been moved out
+/// or dropped.
+///
+/// # Safety
+///
+/// The same as for [`take_node`].
+unsafe fn release_node<T>(node: Link<T>) {
+    wipe_node(node);
+    dealloc(node.cast(), Layout::new::<Node<T>>());
+}
+
+/// `wipe_node` frees the list of children of a node whose value has already
+/// been moved out or dropped, and wipes the value with the `zeroize` feature.
+/// Only the allocation of the node itself is left.
+///
+/// # Safety
+///
+/// The same as for [`take_node`].
+unsafe fn wipe_node<T>(node: Link<T>) {
+    ptr::drop_in_place(ptr::addr_of_mut!((*node).children));
+    #[cfg(feature = "zeroize")]
+    zeroize::zeroize_flat_type(ptr::addr_of_mut!((*node).val));
+}
+
+/// `shrink_children` halves the capacity of the
+/// [`children`](Node::children) of `node` once less than a quarter of it is
+/// used, so nodes which lost most of their children to cuts do not keep the
+/// memory for them. Halving instead of shrinking to fit leaves room for a few
+/// children to be linked again without reallocating.
+fn shrink_children<T>(node: Link<T>) {
+    let children = unsafe { &mut (*node).children };
+    if children.len() < children.capacity() / 4 {
+        children.shrink_to(children.capacity() / 2);
+    }
+}
+
+/// `clone_tree` deep copies the tree below `node` and attaches the copy to
+/// `parent`, returning the new root. Nodes are taken from the list `pool` as
+/// long as it has any, and only allocated once it is empty.
+fn clone_tree<T: Clone>(
+    node: Link<T>,
+    parent: Link<T>,
+    pool: &mut Link<T>,
+    wipe: Wiper<T>,
+) -> Link<T> {
+    unsafe {
+        let node = &*node;
+        let new = if pool.is_null() {
+            Box::into_raw(Box::new(Node {
+                parent,
+                children: Vec::with_capacity(node.children.len()),
+                degree:   node.degree,
+                marked:   node.marked,
+                slot:     NO_SLOT,
+                weight:   node.weight,
+                val:      node.val.clone(),
+            }))
+        } else {
+            let new = *pool;
+            *pool = (*new).parent;
+            (*new).parent = parent;
+            (*new).children.reserve(node.children.len());
+            (*new).degree = node.degree;
+            (*new).marked = node.marked;
+            (*new).slot = NO_SLOT;
+            (*new).weight = node.weight;
+            if let Some(wipe) = wipe {
+                wipe(&mut (*new).val);
+            }
+            (*new).val.clone_from(&node.val);
+            new
+        };
+        for &c in &node.children {
+            let child = clone_tree(c, new, pool, wipe);
+            (*new).children.push(child);
+        }
+        new
+    }
+}
+
+/// `pool_tree` appends `node` and all of its descendants in pre-order to a
+/// list threaded through their [`parent`](Node::parent) links, clearing their
+/// children. `tail` points to the link the node is written to and the link
+/// the next node has to be written to is returned.
+fn pool_tree<T>(node: Link<T>, tail: *mut Link<T>) -> *mut Link<T> {
+    unsafe {
+        *tail = node;
+        let mut tail = ptr::addr_of_mut!((*node).parent);
+        for i in 0..(*node).children.len() {
+            tail = pool_tree((&(*node).children)[i], tail);
+        }
+        (*node).children.clear();
+        tail
+    }
+}
+
+/// `insert_root_list` is a helper, that inserts a node into a root_list or
+/// merges them if there already is a node with the same degree in the 
+/// root_list. Equal nodes are linked according to `tie_break`, where `link`
+/// counts as reached after the node already in the root_list.
+fn insert_root_list<T>(link: Link<T>, root_list: &mut [Link<T>], tie_break: TieBreak)
+        -> Link<T>
     where
         T: PartialOrd {
     unsafe {
@@ -367,108 +2753,1096 @@ fn insert_root_list<T>(link: Link<T>, root_list: &mut [Link<T>]) -> Link<T>
             root_list[cur_spot] = link;
             ptr::null_mut()
         } else {
-            let (min, max) = if (*link).val < (*root_list[cur_spot]).val { 
+            let link_wins = match tie_break {
+                TieBreak::First => (*link).val < (*root_list[cur_spot]).val,
+                TieBreak::Last => (*link).val.partial_cmp(&(*root_list[cur_spot]).val)
+                    != Some(Ordering::Greater),
+            };
+            let (min, max) = if link_wins {
                 (link, root_list[cur_spot])
             } else { 
                 (root_list[cur_spot], link)
             };
 
-            (*min).children.push(max);
-            (*min).degree += 1;
-            root_list[cur_spot] = ptr::null_mut();
+            (*max).parent = min;
+            (*min).children.push(max);
+            (*min).degree += 1;
+            (*min).weight += (*max).weight;
+            root_list[cur_spot] = ptr::null_mut();
+
+            min
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FibHeap;
+    use crate::assert_allocs;
+    use crate::testing::CountingAlloc;
+
+    #[global_allocator]
+    static ALLOC: CountingAlloc = CountingAlloc;
+
+    #[test]
+    fn insert_none() {
+        let mut feap = FibHeap::<i32>::new();
+        assert_eq!(feap.get_min(), None);
+        assert_eq!(feap.extract_min(), None);
+    }
+
+    #[test]
+    fn insert_one() {
+        let mut feap = FibHeap::new();
+        feap.insert(10);
+        assert_eq!(feap.get_min(), Some(&10));
+        assert_eq!(feap.extract_min(), Some(10));
+        assert_eq!(feap.extract_min(), None);
+    }
+
+    #[test]
+    fn insert_min_first() {
+        let mut feap = FibHeap::new();
+        feap.insert(10);
+        feap.insert(11);
+        assert_eq!(feap.get_min(), Some(&10));
+    }
+
+    #[test]
+    fn insert_min_last() {
+        let mut feap = FibHeap::new();
+        feap.insert(11);
+        feap.insert(10);
+        assert_eq!(feap.get_min(), Some(&10));
+    }
+
+
+    #[test]
+    fn insert_min() {
+        let mut feap = FibHeap::new();
+        feap.insert(11);
+        feap.insert(10);
+        assert_eq!(feap.get_min(), Some(&10));
+        feap.insert(4);
+        assert_eq!(feap.get_min(), Some(&4));
+    }
+
+    #[test]
+    fn get_min() {
+        let mut feap = FibHeap::new();
+        feap.insert(11);
+        feap.insert(10);
+        feap.insert(4);
+        assert_eq!(feap.get_min(), Some(&4));
+        assert_eq!(feap.extract_min(), Some(4));
+        assert_eq!(feap.get_min(), Some(&10));
+        assert_eq!(feap.extract_min(), Some(10));
+        assert_eq!(feap.get_min(), Some(&11));
+        assert_eq!(feap.extract_min(), Some(11));
+        assert_eq!(feap.get_min(), None);
+        assert_eq!(feap.extract_min(), None);
+    }
+
+    #[test]
+    fn cleanup() {
+        let mut feap = FibHeap::new();
+        feap.insert(0);
+        feap.insert(1);
+        feap.insert(2);
+        feap.insert(3);
+        feap.insert(4);
+        feap.insert(5);
+        assert_eq!(feap.extract_min(), Some(0));
+        assert_eq!(feap.head_list.len(), 2);
+    }
+
+    #[test]
+    fn clear() {
+        let mut feap = FibHeap::new();
+        feap.insert(0);
+        feap.insert(1);
+        feap.insert(2);
+        feap.insert(3);
+        feap.insert(4);
+        feap.clear();
+        assert_eq!(feap.get_min(), None);
+        assert_eq!(feap.head_list.len(), 0);
+    }
+
+    #[test]
+    fn clone() {
+        let mut feap = FibHeap::new();
+        for i in 0..10 {
+            feap.insert(i);
+        }
+        feap.extract_min();
+        let mut cloned = feap.clone();
+        cloned.validate();
+        feap.clear();
+        for i in 1..10 {
+            assert_eq!(cloned.extract_min(), Some(i));
+        }
+        assert_eq!(cloned.extract_min(), None);
+    }
+
+    #[test]
+    fn allocations() {
+        let mut feap = FibHeap::new();
+        for i in 0..50 {
+            assert_allocs!(== 1, { feap.insert(i) });
+        }
+        assert_allocs!(== 0, { feap.get_min() });
+        assert_allocs!(== 0, { feap.decrease_key(&40, -1) });
+        assert_allocs!(== 0, { feap.clear() });
+
+        feap.insert(1);
+        assert_allocs!(== 0, { feap.extract_min() });
+    }
+
+    #[test]
+    fn pop_weighted_random() {
+        let mut feap = FibHeap::new();
+        for i in 0..200 {
+            feap.insert(i);
+        }
+        feap.extract_min();
+
+        // A fixed sequence of "random" numbers walking over the whole range.
+        let mut r = 0.0;
+        let mut rng = || {
+            r = (r + 0.618_034) % 1.0;
+            r
+        };
+        assert_eq!(feap.pop_weighted_random(&mut rng), None);
+        feap.set_weight(Some(|&v| f64::from(v)));
+
+        // Cuts, links and melds all have to keep the sums of the subtrees,
+        // which every operation validates.
+        let handles: Vec<_> = (200..300).map(|i| feap.insert_with_handle(i)).collect();
+        feap.extract_min();
+        for (i, &h) in handles.iter().enumerate().step_by(3) {
+            feap.decrease_key_by_handle(h, 200 + i as i32 / 2);
+        }
+        for &h in handles.iter().skip(1).step_by(7) {
+            feap.delete(h);
+        }
+        let mut other: FibHeap<_> = (300..350).collect();
+        other.extract_min();
+        feap.meld(other);
+        let mut expected: Vec<_> = feap.iter().copied().collect();
+        expected.sort_unstable();
+
+        let mut popped = Vec::new();
+        while let Some(v) = feap.pop_weighted_random(&mut rng) {
+            popped.push(v);
+        }
+        popped.sort_unstable();
+        assert_eq!(popped, expected);
+        assert_eq!(feap.get_min(), None);
+
+        // Values without a weight are never picked, also at the ends of the
+        // range.
+        let mut feap: FibHeap<_> = [0, 1, 10, -3].into_iter().collect();
+        feap.extract_min();
+        feap.set_weight(Some(|&v| f64::from(v)));
+        let mut picked = [feap.pop_weighted_random(|| 0.0), feap.pop_weighted_random(|| 0.999_999)];
+        picked.sort_unstable();
+        assert_eq!(picked, [Some(1), Some(10)]);
+        assert_eq!(feap.pop_weighted_random(&mut rng), None);
+        assert_eq!(feap.len(), 1);
+
+        feap.set_weight(Some(|_| f64::NAN));
+        assert_eq!(feap.pop_weighted_random(&mut rng), None);
+        feap.set_weight(Some(|_| 1.0));
+        assert_eq!(feap.pop_weighted_random(|| 0.999_999), Some(0));
+    }
+
+    #[test]
+    fn add() {
+        let mut evens = FibHeap::new();
+        let mut odds = FibHeap::new();
+        for i in 0..150 {
+            if i % 2 == 0 { evens.insert(i) } else { odds.insert(i) }
+        }
+        evens.extract_min();
+        odds.extract_min();
+
+        let mut all = evens + odds + FibHeap::new();
+        all += FibHeap::new();
+        let mut empty = FibHeap::new();
+        empty += all;
+        let drained: Vec<_> = std::iter::from_fn(|| empty.extract_min()).collect();
+        assert_eq!(drained, (2..150).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn shrink_after_cuts() {
+        let mut feap = FibHeap::new();
+        for i in 0..=64 {
+            feap.insert(i);
+        }
+        // The 64 remaining values form a single tree with 1 at the root.
+        feap.extract_min();
+        let root = feap.min;
+        unsafe {
+            (*root).children.reserve(1000);
+            let capacity = (*root).children.capacity();
+            // Every cut of a child of the root checks its capacity.
+            while (*root).children.len() > 1 {
+                let child = *(*root).children.last().unwrap();
+                feap.decrease_key(&(*child).val.clone(), 0);
+                feap.extract_min();
+            }
+            assert!((*root).children.capacity() < capacity / 4);
+        }
+    }
+
+    #[test]
+    fn clone_from() {
+        let mut template = FibHeap::new();
+        for i in 0..200 {
+            template.insert(i.to_string());
+        }
+        template.extract_min();
+        template.decrease_key("150", String::from("0"));
+
+        let mut scratch = template.clone();
+        for _ in 0..50 {
+            scratch.extract_min();
+        }
+        scratch.clone_from(&template);
+        assert_allocs!(== 0, { scratch.clone_from(&template) });
+
+        let mut smaller = FibHeap::new();
+        smaller.insert(String::from("x"));
+        scratch.clone_from(&smaller);
+        assert_eq!(scratch.extract_min().as_deref(), Some("x"));
+        assert_eq!(scratch.extract_min(), None);
+
+        scratch.clone_from(&template);
+        while let Some(v) = template.extract_min() {
+            assert_eq!(scratch.extract_min(), Some(v));
+        }
+        assert_eq!(scratch.extract_min(), None);
+    }
+
+    #[test]
+    fn tie_break() {
+        use crate::{Keyed, TieBreak};
+
+        for (tie_break, expected) in [(TieBreak::First, "a"), (TieBreak::Last, "b")] {
+            let mut feap = FibHeap::new();
+            feap.set_tie_break(tie_break);
+            feap.insert(Keyed::new(1, "a"));
+            feap.insert(Keyed::new(1, "b"));
+            feap.insert(Keyed::new(0, "z"));
+            feap.extract_min();
+            assert_eq!(feap.head_list.len(), 1);
+            assert_eq!(feap.get_min().map(|k| k.value), Some(expected));
+            assert_eq!(feap.clone().get_min().map(|k| k.value), Some(expected));
+        }
+    }
+
+    #[test]
+    fn lazy_inserts() {
+        let mut feap = FibHeap::new();
+        feap.set_insert_consolidation(false);
+        for i in (0..1000).rev() {
+            feap.insert(i);
+        }
+        assert_eq!(feap.head_list.len(), 1000);
+        feap += FibHeap::new();
+        assert_eq!(feap.head_list.len(), 1000);
+
+        assert_eq!(feap.extract_min(), Some(0));
+        assert!(feap.head_list.len() <= 10);
+        let drained: Vec<_> = std::iter::from_fn(|| feap.extract_min()).collect();
+        assert_eq!(drained, (1..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn into_max_heap() {
+        let mut feap = FibHeap::new();
+        for i in 0..300 {
+            feap.insert(i);
+        }
+        feap.extract_min();
+        feap.decrease_key(&200, -1);
+
+        // Without a consolidation only the lists of the new heap are
+        // allocated, its nodes are the old ones.
+        feap.set_insert_consolidation(false);
+        let mut max = assert_allocs!(<= 3, { feap.into_max_heap() });
+        let top: Vec<_> = (0..3).map(|_| max.extract_min().unwrap().0).collect();
+        assert_eq!(top, [299, 298, 297]);
+
+        let mut min = max.into_min_heap();
+        assert_eq!(min.extract_min(), Some(-1));
+        assert_eq!(min.extract_min(), Some(1));
+    }
+
+    #[test]
+    fn extract_min_budgeted() {
+        use core::task::Poll;
+
+        // The model scan makes this quadratic, which is too slow under Miri.
+        let n = if cfg!(miri) { 200 } else { 2000 };
+        let mut feap = FibHeap::new();
+        feap.set_insert_consolidation(false);
+        let mut model: Vec<i32> = (0..n).rev().collect();
+        for &i in &model {
+            feap.insert(i);
+        }
+
+        // Everything else keeps working between the polls.
+        let mut pending = 0;
+        let mut next = n;
+        while !model.is_empty() {
+            match feap.extract_min_budgeted(50) {
+                Poll::Pending => {
+                    pending += 1;
+                    if pending % 3 == 0 {
+                        feap.insert(next);
+                        if pending % 2 == 0 {
+                            feap.decrease_key(&next, -next);
+                            model.push(-next);
+                        } else {
+                            model.push(next);
+                        }
+                        next += 1;
+                    }
+                }
+                Poll::Ready(min) => {
+                    let idx = (0..model.len()).min_by_key(|&i| model[i]).unwrap();
+                    assert_eq!(min, Some(model.swap_remove(idx)));
+                }
+            }
+        }
+        assert!(pending >= n / 50 - 1);
+        assert_eq!(feap.extract_min_budgeted(0), Poll::Ready(None));
 
-            min
+        // After the first poll the roots only move between the same two
+        // buffers.
+        feap.extend(0..n);
+        assert_eq!(feap.extract_min_budgeted(50), Poll::Pending);
+        let mut buffers = [feap.head_list.as_ptr(), feap.scratch_list.as_ptr()];
+        buffers.sort();
+        while feap.extract_min_budgeted(50).is_pending() {
+            let mut now = [feap.head_list.as_ptr(), feap.scratch_list.as_ptr()];
+            now.sort();
+            assert_eq!(now, buffers);
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::FibHeap;
+    #[test]
+    fn reserve() {
+        let mut feap = FibHeap::new();
+        feap.set_insert_consolidation(false);
+        feap.try_reserve(500).unwrap();
+        assert_eq!(feap.slots.capacity(), 0);
+        feap.try_reserve_handles(250).unwrap();
+        for i in 0..250 {
+            assert_allocs!(== 0, { feap.insert(i) });
+            assert_allocs!(== 0, { feap.insert_with_handle(i) });
+        }
+        assert_allocs!(>= 1, { feap.insert(500) });
+        assert!(feap.try_reserve(usize::MAX).is_err());
+        assert!(feap.try_reserve_handles(usize::MAX).is_err());
+        assert_eq!(feap.extract_min(), Some(0));
+
+        // Reserved nodes which are never used are freed with the heap.
+        let mut feap = FibHeap::<String>::new();
+        feap.reserve(100);
+        feap.insert(String::from("a"));
+        assert_eq!(feap.spare.len(), 99);
+    }
 
     #[test]
-    fn insert_none() {
-        let mut feap = FibHeap::<i32>::new();
-        assert_eq!(feap.get_min(), None);
-        assert_eq!(feap.extract_min(), None);
+    fn extract_min_into() {
+        let mut feap = FibHeap::new();
+        for i in (0..100).rev() {
+            feap.insert(i);
+        }
+
+        let mut batch = Vec::with_capacity(16);
+        let mut seen = Vec::new();
+        loop {
+            batch.clear();
+            let buffer = batch.as_ptr();
+            let n = feap.extract_min_into(&mut batch, 16);
+            assert_eq!(n, batch.len());
+            assert_eq!(batch.as_ptr(), buffer);
+            seen.extend_from_slice(&batch);
+            if n < 16 {
+                break;
+            }
+        }
+        assert_eq!(seen, (0..100).collect::<Vec<_>>());
+        assert_eq!(feap.extract_min_into(&mut batch, 0), 0);
     }
 
     #[test]
-    fn insert_one() {
+    fn split_by() {
         let mut feap = FibHeap::new();
-        feap.insert(10);
-        assert_eq!(feap.get_min(), Some(&10));
-        assert_eq!(feap.extract_min(), Some(10));
-        assert_eq!(feap.extract_min(), None);
+        for i in (0..500).rev() {
+            feap.insert(i);
+        }
+        feap.extract_min();
+        feap.decrease_key(&250, -250);
+
+        let mut threes = feap.split_by(|v| v % 3 == 0);
+        let rest: Vec<_> = std::iter::from_fn(|| feap.extract_min()).collect();
+        let threes: Vec<_> = std::iter::from_fn(|| threes.extract_min()).collect();
+        let mut expected: Vec<_> = (1..500).filter(|v| v % 3 != 0 && *v != 250).collect();
+        expected.insert(0, -250);
+        assert_eq!(rest, expected);
+        assert_eq!(threes, (3..500).step_by(3).collect::<Vec<_>>());
+
+        let mut empty = FibHeap::<i32>::new().split_by(|_| true);
+        assert_eq!(empty.extract_min(), None);
     }
 
     #[test]
-    fn insert_min_first() {
+    fn len() {
         let mut feap = FibHeap::new();
-        feap.insert(10);
-        feap.insert(11);
-        assert_eq!(feap.get_min(), Some(&10));
+        for i in 0..300 {
+            feap.insert(i);
+        }
+        assert_eq!(feap.len(), 300);
+        feap.extract_min();
+        feap.decrease_key(&150, -1);
+        assert_eq!(feap.len(), 299);
+
+        let mut odd = feap.split_by(|v| v % 2 == 1);
+        assert_eq!((feap.len(), odd.len()), (149, 150));
+        odd += feap.clone();
+        assert_eq!(odd.len(), 299);
+        odd.set_weight(Some(|_| 1.0));
+        assert_eq!(odd.pop_weighted_random(|| 0.5).map(|_| odd.len()), Some(298));
+        assert_eq!(odd.into_max_heap().len(), 298);
+
+        feap.clear();
+        assert!(feap.is_empty());
     }
 
     #[test]
-    fn insert_min_last() {
+    fn handles() {
         let mut feap = FibHeap::new();
-        feap.insert(11);
-        feap.insert(10);
-        assert_eq!(feap.get_min(), Some(&10));
+        let handles: Vec<_> = (0..300).map(|i| feap.insert_with_handle(i)).collect();
+        for i in 300..400 {
+            feap.insert(i);
+        }
+        assert_eq!(feap.extract_min(), Some(0));
+        assert_eq!(feap.get(handles[0]), None);
+        for (i, &h) in handles.iter().enumerate().skip(1) {
+            assert_eq!(feap.get(h), Some(&i));
+        }
+
+        // The freed slot is reused, but the old handle stays invalid.
+        let reused = feap.insert_with_handle(1000);
+        assert_eq!(reused.slot, handles[0].slot);
+        assert_eq!(feap.get(handles[0]), None);
+        assert_eq!(feap.get(reused), Some(&1000));
+
+        // Values moved to another heap take no handles along.
+        let mut evens = feap.split_by(|v| v % 2 == 0);
+        assert_eq!(feap.get(handles[2]), None);
+        assert_eq!(feap.get(handles[3]), Some(&3));
+        assert_eq!(evens.get(handles[2]), None);
+        let other = evens.insert_with_handle(2);
+        feap += evens;
+        assert_eq!(feap.get(other), None);
+        assert_eq!(feap.get(handles[3]), Some(&3));
+
+        let cloned = feap.clone();
+        assert_eq!(cloned.get(handles[3]), None);
+        feap.clear();
+        assert_eq!(feap.get(handles[3]), None);
     }
 
+    #[test]
+    fn decrease_key_by_handle() {
+        let mut feap = FibHeap::new();
+        let handles: Vec<_> = (0..500).map(|i| feap.insert_with_handle(i)).collect();
+        feap.extract_min();
+
+        // Decrease from the bottom up, so most nodes are below their parents.
+        for (i, &h) in handles.iter().enumerate().skip(1).rev() {
+            feap.decrease_key_by_handle(h, i as i32 - 1000);
+            assert_eq!(feap.get_min(), Some(&(i as i32 - 1000)));
+        }
+        feap.decrease_key_by_handle(handles[0], -5000);
+        assert_eq!(feap.get_min(), Some(&-999));
+
+        let drained: Vec<_> = std::iter::from_fn(|| feap.extract_min()).collect();
+        assert_eq!(drained, (1..500).map(|i| i - 1000).collect::<Vec<_>>());
+    }
 
     #[test]
-    fn insert_min() {
+    fn delete() {
         let mut feap = FibHeap::new();
-        feap.insert(11);
-        feap.insert(10);
-        assert_eq!(feap.get_min(), Some(&10));
-        feap.insert(4);
-        assert_eq!(feap.get_min(), Some(&4));
+        let handles: Vec<_> = (0..400).map(|i| feap.insert_with_handle(i)).collect();
+        feap.extract_min();
+
+        // Delete every third value, from roots as well as deep in the trees.
+        for (i, &h) in handles.iter().enumerate().skip(1).step_by(3) {
+            assert_eq!(feap.delete(h), Some(i));
+        }
+        assert_eq!(feap.len(), 399 - 133);
+        let drained: Vec<_> = std::iter::from_fn(|| feap.extract_min()).collect();
+        assert_eq!(drained, (2..400).filter(|i| (i - 1) % 3 != 0).collect::<Vec<_>>());
     }
 
     #[test]
-    fn get_min() {
+    fn try_decrease_key() {
+        use crate::FeapError;
+
         let mut feap = FibHeap::new();
-        feap.insert(11);
-        feap.insert(10);
-        feap.insert(4);
-        assert_eq!(feap.get_min(), Some(&4));
-        assert_eq!(feap.extract_min(), Some(4));
-        assert_eq!(feap.get_min(), Some(&10));
-        assert_eq!(feap.extract_min(), Some(10));
-        assert_eq!(feap.get_min(), Some(&11));
-        assert_eq!(feap.extract_min(), Some(11));
-        assert_eq!(feap.get_min(), None);
-        assert_eq!(feap.extract_min(), None);
+        for i in 0..50 {
+            feap.insert(i * 2);
+        }
+        feap.extract_min();
+
+        assert_eq!(feap.try_decrease_key(&7, 1), Err(FeapError::NotFound));
+        assert_eq!(feap.try_decrease_key(&40, 41), Err(FeapError::NewKeyGreater));
+        assert_eq!(feap.try_decrease_key(&40, 40), Ok(()));
+        assert_eq!(feap.try_decrease_key(&40, 1), Ok(()));
+        assert_eq!(feap.get_min(), Some(&1));
+
+        // The infallible variants ignore greater values instead of breaking
+        // the order of the children.
+        feap.decrease_key(&1, 1000);
+        let ninety = feap.insert_with_handle(90);
+        feap.decrease_key_by_handle(ninety, 1000);
+        assert_eq!(feap.get(ninety), Some(&90));
+        assert_eq!(feap.extract_min(), Some(1));
+        assert_eq!(FeapError::NotFound.to_string(), "no matching value in the heap");
+
+        let mut floats = FibHeap::new();
+        floats.insert(1.0);
+        assert_eq!(floats.try_decrease_key(&1.0, f64::NAN), Err(FeapError::NewKeyGreater));
     }
 
     #[test]
-    fn cleanup() {
+    fn remove() {
         let mut feap = FibHeap::new();
-        feap.insert(0);
-        feap.insert(1);
-        feap.insert(2);
-        feap.insert(3);
-        feap.insert(4);
-        feap.insert(5);
-        assert_eq!(feap.extract_min(), Some(0));
-        assert_eq!(feap.head_list.len(), 2);
+        for i in 0..300 {
+            feap.insert(i % 100);
+        }
+        feap.extract_min();
+
+        for i in (1..100).step_by(2) {
+            assert_eq!(feap.remove(&i), Some(i));
+        }
+        assert_eq!(feap.remove(&1000), None);
+        assert_eq!(feap.len(), 299 - 50);
+
+        let drained: Vec<_> = std::iter::from_fn(|| feap.extract_min()).collect();
+        let mut expected: Vec<_> = (0..300).map(|i| i % 100).collect();
+        expected.sort_unstable();
+        expected.remove(0);
+        for i in (1..100).step_by(2) {
+            let idx = expected.binary_search(&i).unwrap();
+            expected.remove(idx);
+        }
+        assert_eq!(drained, expected);
     }
 
     #[test]
-    fn clear() {
+    fn contains() {
         let mut feap = FibHeap::new();
-        feap.insert(0);
-        feap.insert(1);
-        feap.insert(2);
-        feap.insert(3);
-        feap.insert(4);
-        feap.clear();
-        assert_eq!(feap.get_min(), None);
-        assert_eq!(feap.head_list.len(), 0);
+        for i in 0..200 {
+            feap.insert(i);
+        }
+        feap.extract_min();
+        feap.decrease_key(&150, -150);
+
+        // Values at every depth of the trees are found.
+        assert!((1..200).filter(|&i| i != 150).all(|i| feap.contains(&i)));
+        assert!(feap.contains(&-150));
+        assert!(!feap.contains(&0));
+        assert!(!feap.contains(&150));
+        assert!(!FibHeap::<i32>::new().contains(&0));
+    }
+
+    #[test]
+    fn count() {
+        let mut feap = FibHeap::new();
+        for i in 0..500 {
+            feap.insert(i % 7);
+        }
+        feap.extract_min();
+        feap.decrease_key(&6, 0);
+
+        assert_eq!(feap.count(&0), 72);
+        assert_eq!(feap.count(&3), 71);
+        assert_eq!(feap.count(&6), 70);
+        assert_eq!(feap.count(&7), 0);
+        assert_eq!((0..7).map(|i| feap.count(&i)).sum::<usize>(), feap.len());
+    }
+
+    #[test]
+    fn meld() {
+        let mut small = FibHeap::new();
+        let kept = small.insert_with_handle(50);
+        let mut large = FibHeap::new();
+        large.set_insert_consolidation(false);
+        for i in 0..500 {
+            large.insert(i + 100);
+        }
+        large.insert_with_handle(1);
+        large.insert_with_handle(1000);
+
+        let moved = large.insert_with_handle(2);
+
+        small.meld(large);
+        assert_eq!(small.len(), 504);
+        // The roots of `large` were chained, not copied.
+        assert_eq!(small.head_list.len(), 1);
+        assert_eq!(small.root_lists().map(<[_]>::len).sum::<usize>(), 504);
+        assert_eq!(small.iter().count(), 504);
+        assert_eq!(small.get_min(), Some(&1));
+        assert_eq!(small.get(kept), Some(&50));
+        // The slots of `large` did not come along. The handle of `moved`
+        // names slot 2, which this heap has not issued.
+        assert_eq!(small.slots.len(), 1);
+        assert_eq!(small.get(moved), None);
+        let reused = small.insert_with_handle(3);
+        assert_eq!(small.delete(reused), Some(3));
+        assert_eq!(small.get(kept), Some(&50));
+
+        // Neither the roots nor the slots of the melded heap are walked, so
+        // melding a large heap takes as much as melding a small one: a
+        // single allocation for the link of its root list.
+        for n in [10, 10_000] {
+            let mut big = FibHeap::new();
+            big.set_insert_consolidation(false);
+            for i in 0..n {
+                big.insert_with_handle(i + 1000);
+            }
+            let mut chained = FibHeap::new();
+            chained.insert(0);
+            let len = chained.len();
+            assert_allocs!(== 1, { chained.meld(big) });
+            assert_eq!(chained.len(), len + n as usize);
+        }
+
+        assert_eq!(small.extract_min(), Some(1));
+        assert_eq!(small.extract_min(), Some(2));
+        assert_eq!(small.extract_min(), Some(50));
+        assert_eq!(small.extract_min(), Some(100));
+    }
+
+    #[test]
+    fn union() {
+        let mut a = FibHeap::new();
+        a.set_insert_consolidation(false);
+        let handle = a.insert_with_handle(5);
+        let mut b = FibHeap::new();
+        for i in 0..200 {
+            b.insert(i * 2);
+        }
+
+        let mut all = FibHeap::union(a, b);
+        assert_eq!(all.len(), 201);
+        assert_eq!(all.get(handle), Some(&5));
+        assert!(!all.consolidate_on_insert);
+        assert_eq!(all.extract_min(), Some(0));
+        assert_eq!(all.extract_min(), Some(2));
+        assert_eq!(all.extract_min(), Some(4));
+        assert_eq!(all.extract_min(), Some(5));
+
+        // The heap with more roots hands its list over, and its trees still
+        // have to be weighed by the function of the heap they end up in.
+        let mut a = FibHeap::new();
+        a.set_weight(Some(|&v| f64::from(v)));
+        a.insert(1);
+        let b: FibHeap<_> = (10..15).collect();
+        let all = FibHeap::union(a, b);
+        all.validate();
+        let draws = 1000;
+        let ones = (0..draws).filter(|&i| {
+            let r = (f64::from(i) + 0.5) / f64::from(draws);
+            all.clone().pop_weighted_random(|| r) == Some(1)
+        }).count();
+        // 1 has a weight of 1 out of 61.
+        assert!((15..=18).contains(&ones), "1 was picked {ones} times");
+    }
+
+    #[test]
+    fn append() {
+        let mut all = FibHeap::new();
+        let mut batch = FibHeap::new();
+        batch.set_insert_consolidation(false);
+        for round in 0..5 {
+            let stale = batch.insert_with_handle(-1);
+            for i in 0..100 {
+                batch.insert(round * 100 + i);
+            }
+            batch.remove(&-1);
+            assert_eq!(batch.get(stale), None);
+            let handle = batch.insert_with_handle(round * 100);
+            let capacity = batch.slots.capacity();
+
+            all.append(&mut batch);
+            assert!(batch.is_empty());
+            assert_eq!(batch.get(handle), None);
+            assert_eq!(batch.get_min(), None);
+            assert_eq!(batch.slots.capacity(), capacity);
+        }
+        assert_eq!(all.len(), 505);
+        let drained: Vec<_> = std::iter::from_fn(|| all.extract_min()).collect();
+        let mut expected: Vec<_> = (0..500).chain((0..5).map(|r| r * 100)).collect();
+        expected.sort_unstable();
+        assert_eq!(drained, expected);
+    }
+
+    #[test]
+    fn extend() {
+        let mut feap = FibHeap::new();
+        feap.insert(1000);
+        feap.extend((0..500).rev());
+        assert!(feap.head_list.len() <= 10);
+        feap.extend(&[-1, -2]);
+        assert_eq!(feap.len(), 503);
+
+        let drained: Vec<_> = std::iter::from_fn(|| feap.extract_min()).collect();
+        let expected: Vec<_> = [-2, -1].into_iter().chain(0..500).chain([1000]).collect();
+        assert_eq!(drained, expected);
+    }
+
+    #[test]
+    fn from_iter() {
+        let mut feap: FibHeap<_> = (0..300).map(|i| (i * 7) % 300).collect();
+        assert_eq!(feap.len(), 300);
+        assert!(feap.head_list.len() <= 10);
+        assert_eq!(feap.get_min(), Some(&0));
+
+        let drained: Vec<_> = std::iter::from_fn(|| feap.extract_min()).collect();
+        assert_eq!(drained, (0..300).collect::<Vec<_>>());
+
+        let empty: FibHeap<i32> = core::iter::empty().collect();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn from_vec() {
+        let vec: Vec<_> = (0..300).rev().collect();
+        // The two lists of an empty heap, growing the head_list once and one
+        // node per value.
+        let mut feap = assert_allocs!(== 303, { FibHeap::from_vec(vec) });
+        assert_eq!(feap.len(), 300);
+        assert_eq!(feap.head_list.len(), 300);
+        assert_eq!(feap.get_min(), Some(&0));
+
+        let drained: Vec<_> = std::iter::from_fn(|| feap.extract_min()).collect();
+        assert_eq!(drained, (0..300).collect::<Vec<_>>());
+
+        let feap: FibHeap<i32> = Vec::new().into();
+        assert!(feap.is_empty());
+    }
+
+    #[test]
+    fn binary_heap() {
+        use std::collections::BinaryHeap;
+
+        let mut feap: FibHeap<_> = (0..300).rev().collect();
+        feap.insert_with_handle(300);
+        feap.extract_min();
+        let heap = BinaryHeap::from(feap);
+        assert_eq!(heap.len(), 300);
+        assert_eq!(heap.into_sorted_vec(), (1..=300).collect::<Vec<_>>());
+
+        let mut feap = FibHeap::from(BinaryHeap::from(vec![3, 1, 2, 1]));
+        assert_eq!(feap.len(), 4);
+        let drained: Vec<_> = std::iter::from_fn(|| feap.extract_min()).collect();
+        assert_eq!(drained, [1, 1, 2, 3]);
+    }
+
+    #[test]
+    fn into_sorted_vec() {
+        let feap: FibHeap<_> = (0..300).map(|i| (i * 7) % 300).collect();
+        let sorted = feap.into_sorted_vec();
+        assert_eq!(sorted.capacity(), 300);
+        assert_eq!(sorted, (0..300).collect::<Vec<_>>());
+
+        assert!(FibHeap::<i32>::new().into_sorted_vec().is_empty());
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut feap: FibHeap<_> = (0..300).rev().collect();
+        let handle = feap.insert_with_handle(-1);
+
+        assert_allocs!(== 0, { assert_eq!(feap.push_pop(-5), -5) });
+        assert_eq!(feap.push_pop(-1), -1);
+        assert_eq!(feap.push_pop(150), -1);
+        assert!(feap.get(handle).is_none());
+        assert_eq!(feap.len(), 301);
+
+        // A stream keeping the 10 largest values.
+        let mut top: FibHeap<_> = (0..10).collect();
+        for v in 10..1000 {
+            top.push_pop(v);
+        }
+        assert_eq!(top.into_sorted_vec(), (990..1000).collect::<Vec<_>>());
+
+        let mut empty = FibHeap::new();
+        assert_eq!(empty.push_pop(1), 1);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn replace_min() {
+        let mut feap: FibHeap<_> = (0..300).rev().collect();
+        feap.extract_min();
+
+        assert_allocs!(== 0, { assert_eq!(feap.replace_min(0), Some(1)) });
+        assert_eq!(feap.replace_min(500), Some(0));
+        assert_eq!(feap.get_min(), Some(&2));
+        assert_eq!(feap.len(), 299);
+
+        let drained: Vec<_> = std::iter::from_fn(|| feap.extract_min()).collect();
+        let expected: Vec<_> = (2..300).chain([500]).collect();
+        assert_eq!(drained, expected);
+
+        assert_eq!(feap.replace_min(7), None);
+        assert_eq!(feap.get_min(), Some(&7));
+    }
+
+    #[test]
+    fn extract_min_n() {
+        let mut feap: FibHeap<_> = (0..300).map(|i| (i * 7) % 300).collect();
+        let handle = feap.insert_with_handle(-1);
+        feap.extract_min();
+        feap.insert(-1);
+        feap.decrease_key(&150, -2);
+
+        assert_eq!(feap.extract_min_n(0), []);
+        assert_eq!(feap.extract_min_n(4), [-2, -1, 0, 1]);
+        assert_eq!(feap.len(), 297);
+        assert!(feap.get(handle).is_none());
+        assert!(feap.head_list.len() <= 10);
+
+        let rest = feap.extract_min_n(1000);
+        assert_eq!(rest.len(), 297);
+        assert!(rest.windows(2).all(|w| w[0] <= w[1]));
+        assert!(feap.is_empty());
+        assert_eq!(feap.extract_min_n(1), []);
+    }
+
+    #[test]
+    fn extract_min_n_unconsolidated() {
+        use std::cell::Cell;
+
+        thread_local! {
+            static COMPARISONS: Cell<usize> = const { Cell::new(0) };
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Counted(u32);
+
+        impl PartialOrd for Counted {
+            fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+                COMPARISONS.set(COMPARISONS.get() + 1);
+                self.0.partial_cmp(&other.0)
+            }
+        }
+
+        // Every value is a root, so scanning all of them for each of the k
+        // minimums would take n * k comparisons.
+        let n = if cfg!(miri) { 2000 } else { 20_000 };
+        let mut feap = FibHeap::from_vec((0..n).rev().map(Counted).collect());
+        assert_eq!(feap.head_list.len(), n as usize);
+
+        COMPARISONS.set(0);
+        let batch = feap.extract_min_n(n as usize / 20);
+        assert!(batch.iter().map(|c| c.0).eq(0..n / 20));
+        let comparisons = COMPARISONS.get();
+        assert!(comparisons < 5 * n as usize, "{comparisons} comparisons");
+    }
+
+    #[test]
+    fn retain() {
+        let mut feap: FibHeap<_> = (0..300).rev().map(Box::new).collect();
+        feap.extract_min();
+        let kept = feap.insert_with_handle(Box::new(1000));
+        let removed = feap.insert_with_handle(Box::new(1001));
+
+        feap.retain(|v| **v % 2 == 0);
+        assert_eq!(feap.len(), 150);
+        assert_eq!(feap.get(kept).map(|v| **v), Some(1000));
+        assert!(feap.get(removed).is_none());
+        assert!(feap.head_list.len() <= 10);
+
+        let drained: Vec<_> = std::iter::from_fn(|| feap.extract_min()).map(|v| *v).collect();
+        let expected: Vec<_> = (2..300).step_by(2).chain([1000]).collect();
+        assert_eq!(drained, expected);
+
+        feap.retain(|_| false);
+        assert!(feap.is_empty());
+    }
+
+    #[test]
+    fn split_by_key() {
+        let mut feap: FibHeap<_> = (0..300).map(|i| (i * 7) % 300).collect();
+        feap.extract_min();
+        feap.decrease_key(&250, -1);
+        let below = feap.insert_with_handle(10);
+        let above = feap.insert_with_handle(200);
+
+        let mut soon = feap.split_by_key(&100);
+        assert_eq!(soon.len(), 101);
+        assert_eq!(feap.len(), 200);
+        assert!(feap.get(below).is_none());
+        assert_eq!(feap.get(above), Some(&200));
+        assert!(soon.head_list.len() <= 10);
+        assert!(feap.head_list.len() <= 10);
+
+        let expected: Vec<_> = [-1].into_iter().chain(1..=10).chain(10..100).collect();
+        assert_eq!(soon.drain_sorted().collect::<Vec<_>>(), expected);
+        let expected: Vec<_> = (100..=200).chain(200..300).filter(|&v| v != 250).collect();
+        assert_eq!(feap.drain_sorted().collect::<Vec<_>>(), expected);
+
+        assert!(feap.split_by_key(&0).is_empty());
+    }
+
+    #[test]
+    fn clear_and_recycle() {
+        use std::rc::Rc;
+
+        let counted = Rc::new(());
+        let mut feap = FibHeap::new();
+        feap.set_insert_consolidation(false);
+        for i in 0..300 {
+            feap.insert((i, Rc::clone(&counted)));
+        }
+        feap.extract_min();
+        let handle = feap.insert_with_handle((1000, Rc::clone(&counted)));
+
+        feap.clear_and_recycle();
+        assert!(feap.is_empty());
+        assert!(feap.get(handle).is_none());
+        assert_eq!(Rc::strong_count(&counted), 1);
+        assert_eq!(feap.spare.len(), 300);
+
+        assert_allocs!(== 0, {
+            for i in (0..300).rev() {
+                feap.insert((i, Rc::clone(&counted)));
+            }
+        });
+        assert_allocs!(== 1, { feap.insert((300, Rc::clone(&counted))) });
+        assert_eq!(feap.extract_min().map(|(v, _)| v), Some(0));
+
+        // Spare nodes are freed with the heap.
+        feap.clear_and_recycle();
+        feap.insert((0, Rc::clone(&counted)));
+        drop(feap);
+        assert_eq!(Rc::strong_count(&counted), 1);
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize_owned_memory() {
+        use zeroize::Zeroize;
+
+        /// A key whose buffer outlives the heap, so it can be checked.
+        #[derive(PartialEq, PartialOrd)]
+        struct Key(&'static mut [u8]);
+
+        impl Zeroize for Key {
+            fn zeroize(&mut self) {
+                self.0.zeroize();
+            }
+        }
+
+        let buffers: Vec<*mut [u8]> = (1..=4u8)
+            .map(|i| Box::into_raw(vec![i; 32].into_boxed_slice()))
+            .collect();
+        let mut feap = FibHeap::new();
+        feap.set_zeroize(true);
+        for &b in &buffers {
+            feap.insert(Key(unsafe { &mut *b }));
+        }
+        let Key(extracted) = feap.extract_min().unwrap();
+        assert_eq!(extracted[0], 1);
+
+        let kept = feap.split_by(|k| k.0[0] == 4);
+        feap.retain(|k| k.0[0] == 2);
+        feap.clear_and_recycle();
+        // The reversed heap still wipes the values it drops.
+        let mut kept = kept.into_max_heap();
+        kept.clear();
+        drop(feap);
+        drop(kept);
+        for b in &buffers[1..] {
+            assert!(unsafe { &**b }.iter().all(|&v| v == 0));
+        }
+        for b in buffers {
+            drop(unsafe { Box::from_raw(b) });
+        }
+    }
+
+    #[test]
+    fn zero_sized() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Clone, PartialEq, PartialOrd)]
+        struct Unit;
+
+        impl Drop for Unit {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let mut feap = FibHeap::new();
+        for _ in 0..250 {
+            assert_allocs!(== 0, { feap.insert(Unit) });
+        }
+        assert!(feap.extract_min().is_some());
+        feap.decrease_key(&Unit, Unit);
+        let cloned = feap.clone();
+        let mut extracted = 1;
+        while feap.extract_min().is_some() {
+            extracted += 1;
+        }
+        assert_eq!(extracted, 250);
+        drop(cloned);
+        // 250 extracted, 249 in the clone, and the replaced value and the
+        // borrowed key of decrease_key.
+        assert_eq!(DROPS.load(Ordering::Relaxed), 250 + 249 + 2);
+
+        // Values with a handle still get a node, the others are counted.
+        let mut units = FibHeap::new();
+        assert_allocs!(== 0, { units.extend(core::iter::repeat_n((), 1000)) });
+        let handle = units.insert_with_handle(());
+        assert_eq!(units.len(), 1001);
+        assert_eq!(units.iter().count(), 1001);
+        assert_eq!(units.count(&()), 1001);
+        assert_eq!(units.get(handle), Some(&()));
+
+        let mut calls = 0;
+        let mut half = units.split_by(|_| {
+            calls += 1;
+            calls % 2 == 0
+        });
+        assert_eq!(units.len() + half.len(), 1001);
+        units.append(&mut half);
+        *units.peek_mut().unwrap() = ();
+        units.set_weight(Some(|_| 1.0));
+        assert_eq!(units.pop_weighted_random(|| 0.5), Some(()));
+        assert_eq!(units.extract_min_n(10).len(), 10);
+        assert_eq!(units.remove(&()), Some(()));
+        units.validate();
+        assert_eq!(units.len(), 989);
+
+        let mut max = units.into_max_heap();
+        assert_eq!(max.drain().take(5).count(), 5);
+        assert!(max.is_empty());
+        assert_eq!(max.extract_min(), None);
     }
 
     #[test]
@@ -477,7 +3851,45 @@ mod tests {
         feap.insert(5);
         feap.insert(10);
         assert_eq!(feap.get_min(), Some(&5));
-        feap.decrease_key(10, 3);
+        feap.decrease_key(&10, 3);
         assert_eq!(feap.get_min(), Some(&3));
     }
+
+    #[test]
+    fn decrease_key_updates_min() {
+        let mut feap = FibHeap::new();
+        for i in 0..=16 {
+            feap.insert(i * 10);
+        }
+        // A single tree of degree 4 with 10 at the root.
+        assert_eq!(feap.extract_min(), Some(0));
+        assert_eq!(feap.head_list.len(), 1);
+
+        // A child which stays above its parent keeps its place.
+        let child = unsafe { (*(&(*feap.min).children)[0]).val };
+        feap.decrease_key(&child, child - 1);
+        assert_eq!(feap.get_min(), Some(&10));
+        assert_eq!(feap.head_list.len(), 1);
+
+        // A child below the root is cut out and becomes the minimum.
+        feap.decrease_key(&160, 5);
+        assert_eq!(feap.get_min(), Some(&5));
+        assert_eq!(feap.head_list.len(), 2);
+
+        // The root itself is decreased in place.
+        feap.decrease_key(&10, 1);
+        assert_eq!(feap.get_min(), Some(&1));
+
+        // A value which is not in the heap changes nothing.
+        feap.decrease_key(&1000, -1);
+        assert_eq!(feap.get_min(), Some(&1));
+
+        let drained: Vec<_> = std::iter::from_fn(|| feap.extract_min()).collect();
+        let mut expected: Vec<_> = (1..16).map(|i| i * 10)
+            .map(|v| if v == child { v - 1 } else { v })
+            .collect();
+        expected[0] = 1;
+        expected.insert(1, 5);
+        assert_eq!(drained, expected);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,183 @@
+//! Merging of sorted runs from readers into a single sorted output, as in the
+//! merge phase of an external sort. This module is only available with the
+//! `io` feature.
+
+use core::cmp::Ordering;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+
+use crate::FibHeap;
+
+/// The default buffer size of every reader and of the writer.
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The current line of one run. Cursors are ordered by their line and then by
+/// the index of the run, so equal lines keep the order of the runs.
+struct Cursor {
+    line: Vec<u8>,
+    run: usize,
+}
+
+impl PartialEq for Cursor {
+    fn eq(&self, other: &Self) -> bool {
+        self.run == other.run && self.line == other.line
+    }
+}
+
+impl PartialOrd for Cursor {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.line.cmp(&other.line).then(self.run.cmp(&other.run)))
+    }
+}
+
+/// Merges any number of line based runs, each sorted bytewise, into one sorted
+/// output. Only the current line of every run is held in memory, in a
+/// [`FibHeap`] keyed by that line.
+///
+/// ```rust
+/// use feap::ExternalMerge;
+///
+/// let runs: Vec<&[u8]> = vec![b"apple\ncherry\n", b"banana\ndate"];
+/// let mut out = Vec::new();
+///
+/// let lines = ExternalMerge::new().buffer_size(4096).merge(runs, &mut out).unwrap();
+/// assert_eq!(lines, 4);
+/// assert_eq!(out, b"apple\nbanana\ncherry\ndate\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExternalMerge {
+    /// The buffer size of every reader.
+    read_buffer: usize,
+
+    /// The buffer size of the writer.
+    write_buffer: usize,
+}
+
+impl Default for ExternalMerge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExternalMerge {
+    /// Create a new [`ExternalMerge`] with 64 KiB buffers.
+    pub fn new() -> Self {
+        Self {
+            read_buffer: DEFAULT_BUFFER_SIZE,
+            write_buffer: DEFAULT_BUFFER_SIZE,
+        }
+    }
+
+    /// Set the buffer size of the readers and the writer to `bytes`.
+    pub fn buffer_size(self, bytes: usize) -> Self {
+        self.read_buffer_size(bytes).write_buffer_size(bytes)
+    }
+
+    /// Set the buffer size of every reader to `bytes`. With many runs the
+    /// readers make up most of the memory used.
+    pub fn read_buffer_size(mut self, bytes: usize) -> Self {
+        self.read_buffer = bytes;
+        self
+    }
+
+    /// Set the buffer size of the writer to `bytes`.
+    pub fn write_buffer_size(mut self, bytes: usize) -> Self {
+        self.write_buffer = bytes;
+        self
+    }
+
+    /// `merge` reads the sorted `runs` line by line and writes all lines to
+    /// `out` in sorted order, returning the number of lines written. Every
+    /// written line ends with a newline, also if the last line of a run did
+    /// not. Equal lines are written in the order of their runs.
+    pub fn merge<R: Read, W: Write>(&self, runs: impl IntoIterator<Item = R>, out: W)
+            -> io::Result<u64> {
+        let mut readers: Vec<_> = runs.into_iter()
+            .map(|r| BufReader::with_capacity(self.read_buffer, r))
+            .collect();
+        let mut out = BufWriter::with_capacity(self.write_buffer, out);
+        let mut heap = FibHeap::new();
+
+        for (run, reader) in readers.iter_mut().enumerate() {
+            let mut line = Vec::new();
+            if read_line(reader, &mut line)? {
+                heap.insert(Cursor { line, run });
+            }
+        }
+
+        let mut lines = 0;
+        while let Some(mut cursor) = heap.extract_min() {
+            out.write_all(&cursor.line)?;
+            out.write_all(b"\n")?;
+            lines += 1;
+
+            // Reuse the buffer of the line just written for the next one.
+            if read_line(&mut readers[cursor.run], &mut cursor.line)? {
+                heap.insert(cursor);
+            }
+        }
+        out.flush()?;
+        Ok(lines)
+    }
+}
+
+/// Reads the next line without its newline into `line`. Returns `false` once
+/// the reader is exhausted.
+fn read_line(reader: &mut impl BufRead, line: &mut Vec<u8>) -> io::Result<bool> {
+    line.clear();
+    if reader.read_until(b'\n', line)? == 0 {
+        return Ok(false);
+    }
+    if line.last() == Some(&b'\n') {
+        line.pop();
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use super::ExternalMerge;
+
+    #[test]
+    fn merges_many_runs() {
+        let mut all: Vec<String> = Vec::new();
+        let runs: Vec<Vec<u8>> = (0..20).map(|r| {
+            let mut run: Vec<String> = (0..50).map(|i| format!("{:05}", (i * 37 + r * 11) % 997))
+                .collect();
+            run.sort();
+            all.extend(run.iter().cloned());
+            run.join("\n").into_bytes()
+        }).collect();
+        all.sort();
+
+        let mut out = Vec::new();
+        let merge = ExternalMerge::new().read_buffer_size(16);
+        let lines = merge.merge(runs.iter().map(|r| r.as_slice()), &mut out).unwrap();
+        assert_eq!(lines, 1000);
+        assert_eq!(String::from_utf8(out).unwrap(), all.join("\n") + "\n");
+    }
+
+    #[test]
+    fn empty_runs() {
+        let mut out = Vec::new();
+        let runs: [&[u8]; 3] = [b"", b"b\n", b""];
+        assert_eq!(ExternalMerge::new().merge(runs, &mut out).unwrap(), 1);
+        assert_eq!(out, b"b\n");
+    }
+
+    /// A reader which fails on the first read.
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk on fire"))
+        }
+    }
+
+    #[test]
+    fn read_errors_are_returned() {
+        let err = ExternalMerge::new().merge([Broken], Vec::new()).unwrap_err();
+        assert_eq!(err.to_string(), "disk on fire");
+    }
+}
@@ -0,0 +1,88 @@
+//! A key made of several components which are compared one after another.
+
+/// A tuple of keys ordered lexicographically: by the first component, then
+/// by the second one for equal first components and so on. This is the
+/// order of the tuple itself. The first component, the primary key, is
+/// returned by `primary`, so
+/// [`decrease_key_by`](crate::FibHeap::decrease_key_by) and
+/// [`remove_by`](crate::FibHeap::remove_by) can find an entry by its primary
+/// key alone, without knowing the tie-breaking components.
+///
+/// If several entries share the primary key, the first one found is changed.
+///
+/// ```rust
+/// use feap::{FibHeap, MultiKey};
+///
+/// // Ordered by deadline, then by submission number.
+/// let mut jobs = FibHeap::new();
+/// jobs.insert(MultiKey((30, 0)));
+/// jobs.insert(MultiKey((20, 1)));
+/// jobs.insert(MultiKey((20, 2)));
+///
+/// jobs.decrease_key_by(|k| *k.primary() == 30, MultiKey((10, 0))).unwrap();
+/// let order: Vec<_> = std::iter::from_fn(|| jobs.extract_min())
+///     .map(|k| k.0)
+///     .collect();
+/// assert_eq!(order, [(10, 0), (20, 1), (20, 2)]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct MultiKey<K>(pub K);
+
+impl<K> MultiKey<K> {
+    /// Create a new [`MultiKey`] from a tuple of its components.
+    pub fn new(key: K) -> Self {
+        Self(key)
+    }
+
+    /// `into_inner` returns the tuple of components.
+    pub fn into_inner(self) -> K {
+        self.0
+    }
+}
+
+impl<K> From<K> for MultiKey<K> {
+    fn from(key: K) -> Self {
+        Self(key)
+    }
+}
+
+/// Implements the access to the primary key for a tuple of the given
+/// component types, the first of which is the primary key.
+macro_rules! primary_key {
+    ($primary:ident $(, $rest:ident)+) => {
+        impl<$primary $(, $rest)+> MultiKey<($primary, $($rest),+)> {
+            /// `primary` returns the first component of the key.
+            pub fn primary(&self) -> &$primary {
+                &self.0.0
+            }
+        }
+    };
+}
+
+primary_key!(A, B);
+primary_key!(A, B, C);
+primary_key!(A, B, C, D);
+
+#[cfg(test)]
+mod tests {
+    use crate::FibHeap;
+    use super::MultiKey;
+
+    #[test]
+    fn lexicographic() {
+        let mut heap = FibHeap::new();
+        for key in [(2, 'b', 0), (1, 'z', 9), (2, 'a', 5), (2, 'a', 1)] {
+            heap.insert(MultiKey::from(key));
+        }
+        heap.decrease_key_by(|k| *k.primary() == 1, MultiKey::new((0, 'z', 9))).unwrap();
+        assert_eq!(heap.get_min().map(|k| k.primary()), Some(&0));
+        let removed = heap.remove_by(|k| *k.primary() == 2).map(MultiKey::into_inner);
+        assert_eq!(removed.map(|k| k.0), Some(2));
+        heap.insert(MultiKey::from(removed.unwrap()));
+
+        let order: Vec<_> = std::iter::from_fn(|| heap.extract_min())
+            .map(MultiKey::into_inner)
+            .collect();
+        assert_eq!(order, [(0, 'z', 9), (2, 'a', 1), (2, 'a', 5), (2, 'b', 0)]);
+    }
+}
@@ -0,0 +1,83 @@
+//! A guard for changing the minimum of a [`FibHeap`] in place.
+
+use core::ops::{Deref, DerefMut};
+
+use crate::FibHeap;
+
+/// The guard returned by [`FibHeap::peek_mut`]. It gives mutable access to the
+/// minimum, and if that was used the heap order is restored on drop. Leaking
+/// the guard after changing the value leaves the heap unordered.
+pub struct PeekMut<'a, T: PartialOrd> {
+    pub(crate) heap: &'a mut FibHeap<T>,
+
+    /// Whether the minimum was borrowed mutably and may have changed.
+    pub(crate) changed: bool,
+}
+
+impl<T: PartialOrd> PeekMut<'_, T> {
+    /// `pop` removes the minimum from the heap and returns it.
+    pub fn pop(mut this: Self) -> T {
+        this.changed = false;
+        this.heap.extract_min().expect("the heap is not empty")
+    }
+}
+
+impl<T: PartialOrd> Deref for PeekMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.heap.get_min().expect("the heap is not empty")
+    }
+}
+
+impl<T: PartialOrd> DerefMut for PeekMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        if self.heap.units > 0 {
+            // A unit has no node whose place in the heap could change, and
+            // takes no memory, so any aligned pointer points to it.
+            return unsafe { core::ptr::NonNull::dangling().as_mut() };
+        }
+        self.changed = true;
+        // The minimum is a node of the heap, which is borrowed mutably.
+        unsafe { &mut (*self.heap.min).val }
+    }
+}
+
+impl<T: PartialOrd> Drop for PeekMut<'_, T> {
+    fn drop(&mut self) {
+        if self.changed {
+            self.heap.restore_min();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FibHeap;
+    use super::PeekMut;
+
+    #[test]
+    fn grown_min_sinks() {
+        let mut feap: FibHeap<_> = (0..300).rev().collect();
+        feap.extract_min();
+        let handle = feap.insert_with_handle(0);
+
+        *feap.peek_mut().unwrap() = 1000;
+        assert_eq!(feap.get_min(), Some(&1));
+        assert_eq!(feap.get(handle), Some(&1000));
+
+        // Only reading does not touch the heap.
+        let head = feap.head_list.clone();
+        assert_eq!(*feap.peek_mut().unwrap(), 1);
+        assert_eq!(feap.head_list, head);
+
+        *feap.peek_mut().unwrap() -= 5;
+        assert_eq!(PeekMut::pop(feap.peek_mut().unwrap()), -4);
+        assert_eq!(feap.len(), 299);
+
+        let drained: Vec<_> = std::iter::from_fn(|| feap.extract_min()).collect();
+        let expected: Vec<_> = (2..300).chain([1000]).collect();
+        assert_eq!(drained, expected);
+        assert!(feap.peek_mut().is_none());
+    }
+}
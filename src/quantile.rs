@@ -0,0 +1,147 @@
+//! Tracking a quantile of a stream of values with two heaps.
+
+use core::cmp::Reverse;
+
+use crate::FibHeap;
+
+/// Tracks the `q`-quantile of a stream of values, using the nearest rank
+/// method: with `n` values seen, the quantile is the `⌈q·n⌉`-th smallest one
+/// (and at least the smallest one).
+///
+/// The values up to the quantile are kept in a max-heap and the ones above it
+/// in a min-heap. After every [`push`](QuantileTracker::push) at most a few
+/// values move between the two to restore the proportion, so pushing is
+/// O(log n) amortized and reading the quantile is O(1).
+///
+/// ```rust
+/// use feap::QuantileTracker;
+///
+/// let mut p90 = QuantileTracker::new(0.9);
+/// for latency in 1..=100 {
+///     p90.push(latency);
+/// }
+/// assert_eq!(p90.quantile(), Some(&90));
+///
+/// let mut median = QuantileTracker::median();
+/// median.push(3.5);
+/// median.push(1.0);
+/// median.push(2.0);
+/// assert_eq!(median.quantile(), Some(&2.0));
+/// ```
+pub struct QuantileTracker<T: PartialOrd> {
+    /// The tracked quantile, in `0.0..=1.0`.
+    q: f64,
+
+    /// The values up to and including the quantile, largest first.
+    lower: FibHeap<Reverse<T>>,
+
+    /// The values above the quantile, smallest first.
+    upper: FibHeap<T>,
+}
+
+impl<T: PartialOrd> QuantileTracker<T> {
+    /// Create a new [`QuantileTracker`] for the `q`-quantile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is not in `0.0..=1.0`.
+    pub fn new(q: f64) -> Self {
+        assert!((0.0..=1.0).contains(&q), "the quantile has to be in 0.0..=1.0");
+        Self {
+            q,
+            lower: FibHeap::new(),
+            upper: FibHeap::new(),
+        }
+    }
+
+    /// Create a new [`QuantileTracker`] for the median.
+    pub fn median() -> Self {
+        Self::new(0.5)
+    }
+
+    /// `push` adds `val` to the stream.
+    pub fn push(&mut self, val: T) {
+        match self.lower.get_min() {
+            Some(Reverse(max)) if val > *max => {
+                self.upper.insert(val);
+            }
+            _ => {
+                self.lower.insert(Reverse(val));
+            }
+        }
+        self.rebalance();
+    }
+
+    /// Moves values between the heaps until the lower one holds exactly the
+    /// values up to the quantile.
+    fn rebalance(&mut self) {
+        let n = self.len();
+        let target = ((self.q * n as f64).ceil() as usize).clamp(1, n);
+        while self.lower.len() > target {
+            let Reverse(val) = self.lower.extract_min().unwrap();
+            self.upper.insert(val);
+        }
+        while self.lower.len() < target {
+            let val = self.upper.extract_min().unwrap();
+            self.lower.insert(Reverse(val));
+        }
+    }
+
+    /// `quantile` returns the current quantile, or `None` if no values have
+    /// been pushed.
+    pub fn quantile(&self) -> Option<&T> {
+        self.lower.get_min().map(|Reverse(v)| v)
+    }
+
+    /// `len` returns the number of values pushed so far.
+    pub fn len(&self) -> usize {
+        self.lower.len() + self.upper.len()
+    }
+
+    /// `is_empty` returns whether no values have been pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `clear` forgets all values.
+    pub fn clear(&mut self) {
+        self.lower.clear();
+        self.upper.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QuantileTracker;
+
+    #[test]
+    fn matches_sorting() {
+        // Sorting after every push is too slow under Miri for many values.
+        let n = if cfg!(miri) { 50 } else { 500 };
+        for q in [0.0, 0.25, 0.5, 0.9, 0.99, 1.0] {
+            let mut tracker = QuantileTracker::new(q);
+            let mut seen = Vec::new();
+            let mut state = 17u32;
+            for _ in 0..n {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let val = state >> 22;
+                tracker.push(val);
+                seen.push(val);
+                seen.sort_unstable();
+                let rank = ((q * seen.len() as f64).ceil() as usize).max(1);
+                assert_eq!(tracker.quantile(), Some(&seen[rank - 1]));
+            }
+            assert_eq!(tracker.len(), n);
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let mut tracker = QuantileTracker::median();
+        assert_eq!(tracker.quantile(), None);
+        tracker.push(1);
+        tracker.clear();
+        assert!(tracker.is_empty());
+        assert_eq!(tracker.quantile(), None);
+    }
+}
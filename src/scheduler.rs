@@ -0,0 +1,228 @@
+//! A scheduler which keeps one [`FibHeap`] per priority class and picks the
+//! class to serve next by a configurable [`Policy`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::FibHeap;
+
+/// How a [`ClassedScheduler`] picks the class the next item is taken from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Always serve the smallest non-empty class. Larger classes only run
+    /// when all smaller ones are empty.
+    Strict,
+
+    /// Serve the non-empty classes in proportion to their weights, using a
+    /// smooth weighted round robin so a heavy class does not run in bursts.
+    /// A class which runs empty starts over without any credit once it gets
+    /// items again.
+    Weighted,
+}
+
+/// The queue of a single class.
+struct Class<T: PartialOrd> {
+    heap: FibHeap<T>,
+    weight: u32,
+
+    /// The running credit of the smooth weighted round robin. It is reset
+    /// when the class runs dry, so a class which comes back starts fresh.
+    credit: i64,
+}
+
+impl<T: PartialOrd> Class<T> {
+    fn new() -> Self {
+        Self {
+            heap: FibHeap::new(),
+            weight: 1,
+            credit: 0,
+        }
+    }
+}
+
+/// A scheduler with one [`FibHeap`] per priority class `P`. Within a class,
+/// items come out smallest first. Which class is served is decided by the
+/// [`Policy`].
+///
+/// ```rust
+/// use feap::{ClassedScheduler, Policy};
+///
+/// let mut sched = ClassedScheduler::new(Policy::Weighted);
+/// sched.set_weight("interactive", 3);
+/// for job in 0..4 {
+///     sched.push("interactive", job);
+///     sched.push("batch", job);
+/// }
+///
+/// let order: Vec<_> = std::iter::from_fn(|| sched.pop()).take(4).collect();
+/// assert_eq!(order, [("interactive", 0), ("batch", 0), ("interactive", 1),
+///     ("interactive", 2)]);
+/// ```
+pub struct ClassedScheduler<P: Ord, T: PartialOrd> {
+    /// The queues of all classes which have been used so far.
+    classes: BTreeMap<P, Class<T>>,
+
+    /// The classes which have queued items, so picking one does not look at
+    /// the empty ones.
+    active: BTreeSet<P>,
+
+    /// How the next class is picked.
+    policy: Policy,
+
+    /// The number of queued items over all classes.
+    len: usize,
+}
+
+impl<P: Ord + Clone, T: PartialOrd> ClassedScheduler<P, T> {
+    /// Create a new, empty [`ClassedScheduler`] with the given [`Policy`].
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            classes: BTreeMap::new(),
+            active: BTreeSet::new(),
+            policy,
+            len: 0,
+        }
+    }
+
+    /// `set_weight` sets the weight of `class` for [`Policy::Weighted`]. Every
+    /// class starts with a weight of 1 and a weight of 0 is treated as 1.
+    pub fn set_weight(&mut self, class: P, weight: u32) {
+        self.classes.entry(class).or_insert_with(Class::new).weight = weight.max(1);
+    }
+
+    /// `push` queues `item` in `class`.
+    pub fn push(&mut self, class: P, item: T) {
+        let queue = self.classes.entry(class.clone()).or_insert_with(Class::new);
+        if queue.heap.is_empty() {
+            self.active.insert(class);
+        }
+        queue.heap.insert(item);
+        self.len += 1;
+    }
+
+    /// `pop` picks a class according to the [`Policy`] and removes its
+    /// smallest item.
+    pub fn pop(&mut self) -> Option<(P, T)> {
+        let class = match self.policy {
+            Policy::Strict => self.active.first()?.clone(),
+            Policy::Weighted => self.pick_weighted()?,
+        };
+
+        let queue = self.classes.get_mut(&class)?;
+        let item = queue.heap.extract_min()?;
+        if queue.heap.is_empty() {
+            queue.credit = 0;
+            self.active.remove(&class);
+        }
+        self.len -= 1;
+        Some((class, item))
+    }
+
+    /// Smooth weighted round robin: every non-empty class earns its weight,
+    /// the one with the most credit is picked and pays the total weight.
+    fn pick_weighted(&mut self) -> Option<P> {
+        let mut total = 0;
+        let mut best: Option<(&P, i64)> = None;
+        for p in &self.active {
+            let c = self.classes.get_mut(p).expect("active classes have a queue");
+            c.credit += i64::from(c.weight);
+            total += i64::from(c.weight);
+            if best.is_none_or(|(_, credit)| c.credit > credit) {
+                best = Some((p, c.credit));
+            }
+        }
+        let p = best?.0.clone();
+        self.classes.get_mut(&p)?.credit -= total;
+        Some(p)
+    }
+
+    /// `peek` returns the smallest item of `class`.
+    pub fn peek(&self, class: &P) -> Option<&T> {
+        self.classes.get(class).and_then(|c| c.heap.get_min())
+    }
+
+    /// `class_len` returns the number of items queued in `class`.
+    pub fn class_len(&self, class: &P) -> usize {
+        self.classes.get(class).map_or(0, |c| c.heap.len())
+    }
+
+    /// `len` returns the number of queued items over all classes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// `is_empty` returns whether no items are queued.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClassedScheduler, Policy};
+
+    #[test]
+    fn strict() {
+        let mut sched = ClassedScheduler::new(Policy::Strict);
+        sched.push(2, 'x');
+        sched.push(1, 'b');
+        sched.push(1, 'a');
+        assert_eq!(sched.len(), 3);
+        assert_eq!(sched.pop(), Some((1, 'a')));
+        assert_eq!(sched.pop(), Some((1, 'b')));
+        sched.push(0, 'z');
+        assert_eq!(sched.pop(), Some((0, 'z')));
+        assert_eq!(sched.pop(), Some((2, 'x')));
+        assert_eq!(sched.pop(), None);
+        assert!(sched.is_empty());
+        assert!(sched.active.is_empty());
+    }
+
+    #[test]
+    fn weighted() {
+        let mut sched = ClassedScheduler::new(Policy::Weighted);
+        sched.set_weight('a', 5);
+        sched.set_weight('b', 2);
+        for i in 0..1000 {
+            sched.push('a', i);
+            sched.push('b', i);
+            sched.push('c', i);
+        }
+
+        let mut served = [0; 3];
+        for _ in 0..800 {
+            let (class, _) = sched.pop().unwrap();
+            served[(class as u8 - b'a') as usize] += 1;
+        }
+        assert_eq!(served, [500, 200, 100]);
+        assert_eq!(sched.class_len(&'a'), 500);
+        assert_eq!(sched.peek(&'c'), Some(&100));
+
+        // Once a class runs dry the others share its time.
+        while sched.class_len(&'a') > 0 {
+            sched.pop();
+        }
+        let (class, _) = sched.pop().unwrap();
+        assert_ne!(class, 'a');
+    }
+
+    #[test]
+    fn credit_resets_when_empty() {
+        let mut sched = ClassedScheduler::new(Policy::Weighted);
+        sched.set_weight('b', 3);
+        sched.push('a', 0);
+        for i in 0..100 {
+            sched.push('b', i);
+        }
+        let order: String = (0..4).map(|_| sched.pop().unwrap().0).collect();
+        assert_eq!(order, "babb");
+
+        // 'a' ran dry right after it was picked, so it comes back without
+        // the debt of that pick.
+        assert_eq!(sched.classes[&'a'].credit, 0);
+        for i in 1..10 {
+            sched.push('a', i);
+        }
+        let order: String = (0..8).map(|_| sched.pop().unwrap().0).collect();
+        assert_eq!(order, "bbabbbab");
+    }
+}
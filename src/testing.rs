@@ -0,0 +1,562 @@
+//! Utilities for testing code which builds on top of [`FibHeap`]. This module
+//! is only available with the `testing` feature.
+//!
+//! The [`DifferentialHarness`] applies a stream of [`Op`]s to a heap and to a
+//! simple model at the same time and reports the first point where they
+//! disagree, together with a minimized script reproducing the problem.
+//!
+//! ```rust
+//! use feap::FibHeap;
+//! use feap::testing::{DifferentialHarness, Op};
+//!
+//! let ops = [Op::Insert(3), Op::Insert(1), Op::ExtractMin, Op::DecreaseKey(3, 0)];
+//! let harness = DifferentialHarness::<FibHeap<i32>>::new();
+//! assert!(harness.run(&ops).is_ok());
+//! ```
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::Cell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Range;
+use std::alloc::System;
+use std::collections::BTreeMap;
+
+use crate::FibHeap;
+
+/// A single operation of an operation stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op<T> {
+    /// Insert the value.
+    Insert(T),
+
+    /// Extract the minimum and compare it against the model.
+    ExtractMin,
+
+    /// Decrease the first value to the second one. Operations which would
+    /// increase the value are skipped, as they are not a valid use of
+    /// [`decrease_key`](FibHeap::decrease_key).
+    DecreaseKey(T, T),
+
+    /// Remove all values.
+    Clear,
+}
+
+/// The heap interface the [`DifferentialHarness`] drives. It is implemented
+/// for [`FibHeap`] and can be implemented for types wrapping it, so the
+/// wrappers can be checked with the same harness.
+pub trait Heap<T> {
+    /// Insert `val` into the heap.
+    fn insert(&mut self, val: T);
+
+    /// Return a reference to the smallest value.
+    fn get_min(&self) -> Option<&T>;
+
+    /// Remove and return the smallest value.
+    fn extract_min(&mut self) -> Option<T>;
+
+    /// Replace `old_val` by the smaller `new_val`.
+    fn decrease_key(&mut self, old_val: T, new_val: T);
+
+    /// Remove all values.
+    fn clear(&mut self);
+}
+
+impl<T: PartialOrd> Heap<T> for FibHeap<T> {
+    fn insert(&mut self, val: T) {
+        FibHeap::insert(self, val);
+    }
+
+    fn get_min(&self) -> Option<&T> {
+        FibHeap::get_min(self)
+    }
+
+    fn extract_min(&mut self) -> Option<T> {
+        FibHeap::extract_min(self)
+    }
+
+    fn decrease_key(&mut self, old_val: T, new_val: T) {
+        FibHeap::decrease_key(self, &old_val, new_val);
+    }
+
+    fn clear(&mut self) {
+        FibHeap::clear(self);
+    }
+}
+
+/// The reference model, a multiset mapping each value to its multiplicity.
+struct Model<T>(BTreeMap<T, usize>);
+
+impl<T: Ord + Clone> Model<T> {
+    fn insert(&mut self, val: T) {
+        *self.0.entry(val).or_default() += 1;
+    }
+
+    fn remove(&mut self, val: &T) -> bool {
+        match self.0.get_mut(val) {
+            Some(1) => { self.0.remove(val); }
+            Some(count) => *count -= 1,
+            None => return false,
+        }
+        true
+    }
+
+    fn min(&self) -> Option<&T> {
+        self.0.keys().next()
+    }
+
+    fn extract_min(&mut self) -> Option<T> {
+        let min = self.min()?.clone();
+        self.remove(&min);
+        Some(min)
+    }
+}
+
+/// The first point at which the heap and the model disagreed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence<T> {
+    /// The index of the operation in the original stream after which the
+    /// heap and the model disagreed.
+    pub step: usize,
+
+    /// The value the model produced.
+    pub expected: Option<T>,
+
+    /// The value the heap produced.
+    pub actual: Option<T>,
+
+    /// A minimized operation stream which still makes the heap diverge. The
+    /// divergence shows up after its last operation.
+    pub repro: Vec<Op<T>>,
+}
+
+impl<T: fmt::Debug> fmt::Display for Divergence<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "heap diverged from the model after step {}: expected {:?}, got {:?}",
+            self.step, self.expected, self.actual)?;
+        writeln!(f, "minimized repro:")?;
+        for op in &self.repro {
+            match op {
+                Op::Insert(v) => writeln!(f, "    heap.insert({v:?});")?,
+                Op::ExtractMin => writeln!(f, "    heap.extract_min();")?,
+                Op::DecreaseKey(old, new) => {
+                    writeln!(f, "    heap.decrease_key(&{old:?}, {new:?});")?
+                }
+                Op::Clear => writeln!(f, "    heap.clear();")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs operation streams against a heap of type `H` and a model, reporting
+/// the first divergence. After every operation the minimum of both has to
+/// match, as well as every value returned by an extraction.
+pub struct DifferentialHarness<H> {
+    heap: PhantomData<fn() -> H>,
+}
+
+impl<H> Default for DifferentialHarness<H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H> DifferentialHarness<H> {
+    /// Create a new harness. Every run starts from `H::default()`.
+    pub fn new() -> Self {
+        Self { heap: PhantomData }
+    }
+
+    /// `run` applies `ops` to a fresh heap and the model. If they disagree at
+    /// some point, the stream is minimized and the [`Divergence`] is returned.
+    pub fn run<T>(&self, ops: &[Op<T>]) -> Result<(), Divergence<T>>
+        where
+            H: Heap<T> + Default,
+            T: Ord + Clone {
+        let (step, expected, actual) = match self.first_divergence(ops) {
+            Some(d) => d,
+            None => return Ok(()),
+        };
+
+        Err(Divergence {
+            step,
+            expected,
+            actual,
+            repro: self.minimize(ops[..=step].to_vec()),
+        })
+    }
+
+    /// Runs `ops` and returns the index of the first diverging operation
+    /// together with the expected and actual value.
+    #[allow(clippy::type_complexity)]
+    fn first_divergence<T>(&self, ops: &[Op<T>]) -> Option<(usize, Option<T>, Option<T>)>
+        where
+            H: Heap<T> + Default,
+            T: Ord + Clone {
+        let mut heap = H::default();
+        let mut model = Model(BTreeMap::new());
+
+        for (step, op) in ops.iter().enumerate() {
+            match op {
+                Op::Insert(v) => {
+                    heap.insert(v.clone());
+                    model.insert(v.clone());
+                }
+                Op::ExtractMin => {
+                    let expected = model.extract_min();
+                    let actual = heap.extract_min();
+                    if expected != actual {
+                        return Some((step, expected, actual));
+                    }
+                }
+                Op::DecreaseKey(old, new) => {
+                    if new <= old {
+                        heap.decrease_key(old.clone(), new.clone());
+                        if model.remove(old) {
+                            model.insert(new.clone());
+                        }
+                    }
+                }
+                Op::Clear => {
+                    heap.clear();
+                    model.0.clear();
+                }
+            }
+
+            if model.min() != heap.get_min() {
+                return Some((step, model.min().cloned(), heap.get_min().cloned()));
+            }
+        }
+        None
+    }
+
+    /// Shrinks a diverging stream by repeatedly dropping chunks of operations
+    /// as long as the rest still diverges, halving the chunk size each round.
+    fn minimize<T>(&self, mut ops: Vec<Op<T>>) -> Vec<Op<T>>
+        where
+            H: Heap<T> + Default,
+            T: Ord + Clone {
+        let mut chunk = ops.len() / 2;
+        while chunk > 0 {
+            let mut start = 0;
+            while start < ops.len() {
+                let mut candidate = ops.clone();
+                candidate.drain(start..(start + chunk).min(ops.len()));
+                match self.first_divergence(&candidate) {
+                    Some((step, _, _)) => {
+                        candidate.truncate(step + 1);
+                        ops = candidate;
+                    }
+                    None => start += chunk,
+                }
+            }
+            chunk /= 2;
+        }
+        ops
+    }
+}
+
+/// A deterministic, seeded generator of operation streams. The same seed and
+/// settings always produce the same stream, so heap heavy scenarios can be
+/// reproduced in any test suite. [`Op::DecreaseKey`] and [`Op::ExtractMin`]
+/// are only generated for values which are in the heap at that point.
+///
+/// ```rust
+/// use feap::FibHeap;
+/// use feap::testing::{DifferentialHarness, Op, Workload};
+///
+/// let ops: Vec<Op<i32>> = Workload::new(42).keys(0..100).take(1000).collect();
+/// let harness = DifferentialHarness::<FibHeap<i32>>::new();
+/// assert!(harness.run(&ops).is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Workload {
+    /// The state of the splitmix64 generator.
+    state: u64,
+
+    /// The range new values are drawn from.
+    keys: Range<i32>,
+
+    /// The relative weights of insert, extract_min, decrease_key and clear.
+    weights: [u32; 4],
+
+    /// The values currently in the heap the stream is applied to.
+    present: Vec<i32>,
+}
+
+impl Workload {
+    /// Create a new generator from `seed`. By default values are drawn from
+    /// `-1000..1000` and operations are weighted 5:3:2:0 between insert,
+    /// extract_min, decrease_key and clear.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+            keys: -1000..1000,
+            weights: [5, 3, 2, 0],
+            present: Vec::new(),
+        }
+    }
+
+    /// Draw inserted values from `keys`. A small range results in many
+    /// duplicates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `keys` is empty.
+    pub fn keys(mut self, keys: Range<i32>) -> Self {
+        assert!(!keys.is_empty(), "the key range must not be empty");
+        self.keys = keys;
+        self
+    }
+
+    /// Set the relative weights of insert, extract_min, decrease_key and clear
+    /// operations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if all weights are zero.
+    pub fn weights(mut self, insert: u32, extract_min: u32, decrease_key: u32,
+            clear: u32) -> Self {
+        self.weights = [insert, extract_min, decrease_key, clear];
+        assert!(self.weights.iter().any(|&w| w > 0), "not all weights may be zero");
+        self
+    }
+
+    /// The next number of the splitmix64 sequence.
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+impl Iterator for Workload {
+    type Item = Op<i32>;
+
+    fn next(&mut self) -> Option<Op<i32>> {
+        let span = (i64::from(self.keys.end) - i64::from(self.keys.start)) as u64;
+        let total: u32 = self.weights.iter().sum();
+        let mut pick = self.below(u64::from(total)) as u32;
+        let mut kind = 0;
+        while pick >= self.weights[kind] {
+            pick -= self.weights[kind];
+            kind += 1;
+        }
+
+        // Without values in the heap every operation but clear turns into an
+        // insert.
+        if self.present.is_empty() && kind != 3 {
+            kind = 0;
+        }
+
+        let op = match kind {
+            0 => {
+                let val = (i64::from(self.keys.start) + self.below(span) as i64) as i32;
+                self.present.push(val);
+                Op::Insert(val)
+            }
+            1 => {
+                let idx = (0..self.present.len())
+                    .min_by_key(|&i| self.present[i])
+                    .unwrap();
+                self.present.swap_remove(idx);
+                Op::ExtractMin
+            }
+            2 => {
+                let idx = self.below(self.present.len() as u64) as usize;
+                let old = self.present[idx];
+                let new = old.saturating_sub(self.below(span / 4 + 1) as i32);
+                self.present[idx] = new;
+                Op::DecreaseKey(old, new)
+            }
+            _ => {
+                self.present.clear();
+                Op::Clear
+            }
+        };
+        Some(op)
+    }
+}
+
+/// A global allocator which counts the allocations of each thread and
+/// forwards them to [`System`]. Install it in a test binary to use
+/// [`allocations`] and [`assert_allocs!`](crate::assert_allocs):
+///
+/// ```rust
+/// use feap::testing::CountingAlloc;
+///
+/// #[global_allocator]
+/// static ALLOC: CountingAlloc = CountingAlloc;
+/// ```
+///
+/// The counter is thread local, so tests running in parallel do not see each
+/// other's allocations.
+pub struct CountingAlloc;
+
+thread_local! {
+    /// The number of allocations made by the current thread.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|a| a.set(a.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// `allocations` returns the number of allocations (including reallocations)
+/// the current thread has made so far. This is only counted if
+/// [`CountingAlloc`] is the global allocator.
+pub fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// `counting_installed` checks whether [`CountingAlloc`] is the global
+/// allocator by making an allocation and watching the counter.
+pub fn counting_installed() -> bool {
+    let before = allocations();
+    drop(std::hint::black_box(Box::new(0u8)));
+    allocations() != before
+}
+
+/// Evaluates a block and asserts that the number of allocations it made
+/// compares to the given count with the given operator. The value of the
+/// block is returned. Requires [`CountingAlloc`] to be the global allocator.
+///
+/// ```rust
+/// use feap::{assert_allocs, FibHeap};
+/// use feap::testing::CountingAlloc;
+///
+/// #[global_allocator]
+/// static ALLOC: CountingAlloc = CountingAlloc;
+///
+/// let mut feap = FibHeap::new();
+/// assert_allocs!(== 1, { feap.insert(10) });
+/// let min = assert_allocs!(<= 0, { feap.extract_min() });
+/// assert_eq!(min, Some(10));
+/// ```
+#[macro_export]
+macro_rules! assert_allocs {
+    ($op:tt $count:expr, $body:block) => {{
+        assert!($crate::testing::counting_installed(),
+            "assert_allocs! needs feap::testing::CountingAlloc as the global allocator");
+        let before = $crate::testing::allocations();
+        let ret = $body;
+        let allocs = $crate::testing::allocations() - before;
+        assert!(allocs $op $count, "expected allocations {} {}, but {} were made",
+            stringify!($op), $count, allocs);
+        ret
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FibHeap;
+    use super::{DifferentialHarness, Divergence, Heap, Op, Workload};
+
+    /// A broken heap which silently drops every inserted `1000`.
+    #[derive(Default)]
+    struct Unlucky(FibHeap<i32>);
+
+    impl Heap<i32> for Unlucky {
+        fn insert(&mut self, val: i32) {
+            if val != 1000 {
+                self.0.insert(val);
+            }
+        }
+
+        fn get_min(&self) -> Option<&i32> {
+            self.0.get_min()
+        }
+
+        fn extract_min(&mut self) -> Option<i32> {
+            self.0.extract_min()
+        }
+
+        fn decrease_key(&mut self, old_val: i32, new_val: i32) {
+            self.0.decrease_key(&old_val, new_val);
+        }
+
+        fn clear(&mut self) {
+            self.0.clear();
+        }
+    }
+
+    fn ops() -> Vec<Op<i32>> {
+        Workload::new(7).keys(-50..50).take(500).collect()
+    }
+
+    #[test]
+    fn fibheap_agrees() {
+        let harness = DifferentialHarness::<FibHeap<i32>>::new();
+        assert_eq!(harness.run(&ops()), Ok(()));
+    }
+
+    #[test]
+    fn workload_is_deterministic() {
+        let a: Vec<_> = Workload::new(1).weights(4, 2, 2, 1).take(300).collect();
+        let b: Vec<_> = Workload::new(1).weights(4, 2, 2, 1).take(300).collect();
+        let c: Vec<_> = Workload::new(2).weights(4, 2, 2, 1).take(300).collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(a.contains(&Op::Clear));
+
+        let harness = DifferentialHarness::<FibHeap<i32>>::new();
+        assert_eq!(harness.run(&a), Ok(()));
+    }
+
+    #[test]
+    fn divergence_is_minimized() {
+        let mut ops = ops();
+        ops.push(Op::Insert(1000));
+        ops.push(Op::Insert(-100));
+        ops.push(Op::ExtractMin);
+        ops.push(Op::Clear);
+        ops.push(Op::Insert(1000));
+
+        let harness = DifferentialHarness::<Unlucky>::new();
+        let divergence = harness.run(&ops).unwrap_err();
+        assert_eq!(divergence.step, ops.len() - 1);
+        assert_eq!(divergence.expected, Some(1000));
+        assert_eq!(divergence.actual, None);
+        assert_eq!(divergence.repro, vec![Op::Insert(1000)]);
+        assert_eq!(divergence.to_string(), "heap diverged from the model after step \
+            504: expected Some(1000), got None\nminimized repro:\n    heap.insert(1000);\n");
+    }
+
+    #[test]
+    fn repro_prints_every_op() {
+        let divergence = Divergence {
+            step: 3,
+            expected: Some(1),
+            actual: Some(2),
+            repro: vec![Op::Insert(5), Op::DecreaseKey(5, 1), Op::ExtractMin, Op::Clear],
+        };
+        assert_eq!(divergence.to_string(), "heap diverged from the model after step \
+            3: expected Some(1), got Some(2)\nminimized repro:\n    heap.insert(5);\n    \
+            heap.decrease_key(&5, 1);\n    heap.extract_min();\n    heap.clear();\n");
+    }
+}
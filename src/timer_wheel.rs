@@ -0,0 +1,319 @@
+//! A hierarchical timer wheel for near-future deadlines which overflows into
+//! a [`FibHeap`](crate::FibHeap) based [`EventQueue`] for deadlines further
+//! away.
+
+use crate::EventQueue;
+
+/// The number of bits of a deadline which select the slot within a level.
+const SLOT_BITS: u32 = 6;
+
+/// The number of slots of each level.
+const SLOTS: usize = 1 << SLOT_BITS;
+
+/// The number of levels of the wheel. Together they cover the deadlines which
+/// differ from the current tick in the lowest [`SPAN_BITS`] bits only.
+const LEVELS: usize = 4;
+
+/// The number of low bits of a deadline the levels of the wheel cover.
+/// Deadlines further away are kept in the overflow heap until they come into
+/// range.
+const SPAN_BITS: u32 = SLOT_BITS * LEVELS as u32;
+
+/// A pending timer: its deadline, the sequence number of its scheduling and
+/// its event.
+type Timer<E> = (u64, u64, E);
+
+/// A timer queue for workloads with many near-future deadlines, built as a
+/// hierarchical timer wheel. Each of the [`LEVELS`] levels has [`SLOTS`]
+/// slots. A slot of the lowest level covers a single tick, and a slot of each
+/// higher level covers as many ticks as the whole level below it. A timer is
+/// kept in the lowest level whose slot range reaches from the current tick to
+/// its deadline. Once time reaches the first tick of a slot on a higher level,
+/// its timers are cascaded into the levels below. Scheduling and firing are
+/// O(1), and every timer is moved at most once per level.
+///
+/// Deadlines beyond the 2^24 ticks the wheel covers are kept in an
+/// [`EventQueue`] and migrated into the wheel as time advances.
+///
+/// Timers which are due at the same tick fire in the order they were
+/// scheduled. Timers scheduled for a tick which has already passed fire on the
+/// next [`advance`](TimerWheel::advance), with the deadline they were given.
+///
+/// ```rust
+/// use feap::TimerWheel;
+///
+/// let mut timers = TimerWheel::new(0);
+/// timers.schedule(10, "near");
+/// timers.schedule(100_000, "far");
+/// timers.schedule(1 << 40, "farthest");
+/// timers.schedule(3, "nearer");
+///
+/// assert_eq!(timers.advance(50), [(3, "nearer"), (10, "near")]);
+/// assert_eq!(timers.next_deadline(), Some(100_000));
+/// assert_eq!(timers.advance(100_000), [(100_000, "far")]);
+/// assert_eq!(timers.advance(u64::MAX), [(1 << 40, "farthest")]);
+/// ```
+pub struct TimerWheel<E> {
+    /// The timers of every slot of every level, indexed by the level and then
+    /// by the bits of their deadline which belong to that level.
+    levels: Vec<Vec<Vec<Timer<E>>>>,
+
+    /// A bit for each slot of each level which holds any timers.
+    occupied: [u64; LEVELS],
+
+    /// The number of timers in [`levels`](TimerWheel::levels).
+    wheel_len: usize,
+
+    /// The number of timers in [`overflow`](TimerWheel::overflow).
+    overflow_len: usize,
+
+    /// The first tick which has not fired yet.
+    now: u64,
+
+    /// The sequence number the next scheduled timer gets.
+    next_seq: u64,
+
+    /// The timers whose deadlines are out of the range of the wheel, together
+    /// with their sequence numbers.
+    overflow: EventQueue<u64, (u64, E)>,
+}
+
+/// `level_of` returns the level a timer due at `deadline` belongs to at the
+/// tick `now`, which is [`LEVELS`] or more for the overflow heap.
+fn level_of(now: u64, deadline: u64) -> usize {
+    let masked = (now ^ deadline) | (SLOTS as u64 - 1);
+    ((63 - masked.leading_zeros()) / SLOT_BITS) as usize
+}
+
+/// `slot_of` returns the slot of `tick` within `level`.
+fn slot_of(tick: u64, level: usize) -> usize {
+    (tick >> (SLOT_BITS * level as u32)) as usize & (SLOTS - 1)
+}
+
+impl<E> TimerWheel<E> {
+    /// Create a new [`TimerWheel`] whose first tick is `now`.
+    pub fn new(now: u64) -> Self {
+        Self {
+            levels: (0..LEVELS).map(|_| (0..SLOTS).map(|_| Vec::new()).collect()).collect(),
+            occupied: [0; LEVELS],
+            wheel_len: 0,
+            overflow_len: 0,
+            now,
+            next_seq: 0,
+            overflow: EventQueue::new(),
+        }
+    }
+
+    /// `now` returns the first tick which has not fired yet.
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// `schedule` adds a timer firing at `deadline`. Deadlines in the past
+    /// fire on the next [`advance`](TimerWheel::advance), ahead of the timers
+    /// which are due then, and keep their own deadline.
+    pub fn schedule(&mut self, deadline: u64, event: E) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.place((deadline, seq, event));
+    }
+
+    /// `place` puts a timer into its slot, or into the overflow heap if it is
+    /// out of the range of the wheel. A timer whose deadline has passed goes
+    /// into the slot of the current tick.
+    fn place(&mut self, timer: Timer<E>) {
+        let (deadline, seq, event) = timer;
+        let tick = deadline.max(self.now);
+        let level = level_of(self.now, tick);
+        if level >= LEVELS {
+            self.overflow.schedule(deadline, (seq, event));
+            self.overflow_len += 1;
+            return;
+        }
+        let slot = slot_of(tick, level);
+        self.levels[level][slot].push((deadline, seq, event));
+        self.occupied[level] |= 1 << slot;
+        self.wheel_len += 1;
+    }
+
+    /// `next_slot` returns the level and the slot of the earliest timers in
+    /// the wheel, together with the first tick of that slot. The slots of the
+    /// lower levels all come before the next slot of a higher level, so the
+    /// lowest level with any timers ahead of the current tick has them.
+    fn next_slot(&self) -> Option<(usize, usize, u64)> {
+        (0..LEVELS).find_map(|level| {
+            let ahead = self.occupied[level] & (u64::MAX << slot_of(self.now, level));
+            if ahead == 0 {
+                return None;
+            }
+            let slot = ahead.trailing_zeros() as usize;
+            let shift = SLOT_BITS * level as u32;
+            let epoch = self.now & !((1 << (shift + SLOT_BITS)) - 1);
+            Some((level, slot, epoch | (slot as u64) << shift))
+        })
+    }
+
+    /// `next_deadline` returns the earliest deadline of all timers.
+    pub fn next_deadline(&self) -> Option<u64> {
+        match self.next_slot() {
+            Some((level, slot, _)) => self.levels[level][slot].iter().map(|t| t.0).min(),
+            None => self.overflow.peek_time().copied(),
+        }
+    }
+
+    /// `advance` fires all timers due at or before `to` and returns them
+    /// ordered by deadline. Ticks without any timers are skipped.
+    pub fn advance(&mut self, to: u64) -> Vec<(u64, E)> {
+        let mut fired = Vec::new();
+        while self.now <= to {
+            let Some((level, slot, start)) = self.next_slot() else {
+                // The wheel is empty, so jump straight to the next overflow
+                // deadline or past `to`.
+                match self.overflow.peek_time() {
+                    Some(&next) if next <= to => {
+                        self.now = next;
+                        self.migrate();
+                        continue;
+                    }
+                    _ => {
+                        self.now = to.saturating_add(1);
+                        self.migrate();
+                        break;
+                    }
+                }
+            };
+            if start > to {
+                self.now = to + 1;
+                break;
+            }
+
+            self.now = self.now.max(start);
+            self.occupied[level] &= !(1 << slot);
+            let mut timers = core::mem::take(&mut self.levels[level][slot]);
+            self.wheel_len -= timers.len();
+            if level == 0 {
+                // Timers cascaded from higher levels may have been scheduled
+                // before the ones which went into this slot directly, and
+                // the ones scheduled in the past come before all of them.
+                timers.sort_by_key(|t| (t.0, t.1));
+                fired.extend(timers.drain(..).map(|(deadline, _, event)| (deadline, event)));
+                self.levels[level][slot] = timers;
+                if self.now == u64::MAX {
+                    break;
+                }
+                self.now += 1;
+                self.migrate();
+            } else {
+                for timer in timers.drain(..) {
+                    self.place(timer);
+                }
+                self.levels[level][slot] = timers;
+            }
+        }
+        fired
+    }
+
+    /// `migrate` moves all overflow timers which came into the range of the
+    /// wheel into their slots.
+    fn migrate(&mut self) {
+        let end = self.now | ((1 << SPAN_BITS) - 1);
+        let due: Vec<_> = self.overflow.pop_until(end).collect();
+        for (deadline, (seq, event)) in due {
+            self.overflow_len -= 1;
+            self.place((deadline, seq, event));
+        }
+    }
+
+    /// `len` returns the number of pending timers.
+    pub fn len(&self) -> usize {
+        self.wheel_len + self.overflow_len
+    }
+
+    /// `is_empty` returns whether there are no pending timers.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TimerWheel, SPAN_BITS};
+
+    #[test]
+    fn fires_in_order() {
+        let mut timers = TimerWheel::new(5);
+        let mut expected = Vec::new();
+        let mut state = 3u64;
+        for i in 0..2000 {
+            state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let deadline = 5 + (state >> 33) % 5000;
+            timers.schedule(deadline, i);
+            expected.push((deadline, i));
+        }
+        expected.sort_by_key(|&(d, i)| (d, i));
+
+        let mut fired = Vec::new();
+        for to in (0..6000).step_by(97) {
+            fired.extend(timers.advance(to));
+            assert!(fired.iter().all(|&(d, _)| d <= to));
+        }
+        assert_eq!(fired, expected);
+        assert!(timers.is_empty());
+    }
+
+    #[test]
+    fn same_tick_is_fifo() {
+        let mut timers = TimerWheel::new(0);
+        timers.schedule(1000, 'a');
+        timers.advance(900);
+        timers.schedule(1000, 'b');
+        timers.schedule(1000, 'c');
+        assert_eq!(timers.len(), 3);
+        assert_eq!(timers.advance(1000), [(1000, 'a'), (1000, 'b'), (1000, 'c')]);
+        assert_eq!(timers.now(), 1001);
+    }
+
+    #[test]
+    fn past_deadlines_are_kept() {
+        let mut timers = TimerWheel::new(0);
+        timers.schedule(901, 'a');
+        timers.advance(900);
+        timers.schedule(2, 'b');
+        timers.schedule(901, 'c');
+        timers.schedule(0, 'd');
+        assert_eq!(timers.next_deadline(), Some(0));
+        assert_eq!(timers.advance(901), [(0, 'd'), (2, 'b'), (901, 'a'), (901, 'c')]);
+    }
+
+    #[test]
+    fn far_timers_cascade() {
+        let span = 1u64 << SPAN_BITS;
+        let mut timers = TimerWheel::new(0);
+        let mut expected = Vec::new();
+        let mut state = 11u64;
+        for i in 0..1000 {
+            state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let deadline = (state >> 20) % (4 * span);
+            timers.schedule(deadline, i);
+            expected.push((deadline, i));
+        }
+        // Only the deadlines beyond the first span wait in the heap.
+        let beyond = expected.iter().filter(|&&(d, _)| d >= span).count();
+        assert_eq!(timers.overflow_len, beyond);
+        expected.sort_by_key(|&(d, i)| (d, i));
+
+        let mut fired = Vec::new();
+        let mut to = 0;
+        while !timers.is_empty() {
+            to += span / 7;
+            fired.extend(timers.advance(to));
+            assert!(fired.iter().all(|&(d, _)| d <= to));
+            assert!(timers.next_deadline().is_none_or(|d| d > to));
+        }
+        assert_eq!(fired, expected);
+
+        timers.schedule(u64::MAX, 1000);
+        assert_eq!(timers.advance(u64::MAX), [(u64::MAX, 1000)]);
+        assert!(timers.advance(u64::MAX).is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+//! [Kani](https://github.com/model-checking/kani) proof harnesses for the core
+//! operations. They are only compiled by `cargo kani`, which checks memory
+//! safety of the unsafe code for every possible input up to the given bounds,
+//! plus the assertions below.
+//!
+//! The loops over [`root_list`](FibHeap::root_list) during consolidation run
+//! [`MAX_DEGREE`] times, which is why every harness needs a large unwind
+//! bound even though the heaps themselves are tiny.
+//!
+//! **These harnesses are unverified.** They have not been run with `cargo
+//! kani` yet, which was not available where they were written, so they do not
+//! prove anything until they are. Expect long runs: the unwind bound of 257
+//! unrolls every one of those loops completely.
+
+use crate::{FibHeap, MAX_DEGREE};
+
+/// The maximum number of elements a harness inserts.
+const MAX_LEN: usize = 4;
+
+/// Fills a heap with up to [`MAX_LEN`] arbitrary values and returns it
+/// together with the inserted values.
+fn any_heap() -> (FibHeap<i8>, Vec<i8>) {
+    let len: usize = kani::any();
+    kani::assume(len <= MAX_LEN);
+
+    let mut feap = FibHeap::new();
+    let mut vals = Vec::with_capacity(len);
+    for _ in 0..len {
+        let v: i8 = kani::any();
+        feap.insert(v);
+        vals.push(v);
+    }
+    (feap, vals)
+}
+
+/// Extracting everything yields the inserted values in ascending order.
+#[kani::proof]
+#[kani::unwind(257)]
+fn insert_extract_min() {
+    let (mut feap, mut vals) = any_heap();
+    vals.sort_unstable();
+    assert_eq!(feap.get_min(), vals.first());
+
+    for v in vals {
+        feap.validate();
+        assert_eq!(feap.extract_min(), Some(v));
+    }
+    assert_eq!(feap.extract_min(), None);
+}
+
+/// Decreasing an arbitrary element keeps the heap valid and `min` correct,
+/// also when the element sits below a root after a consolidation.
+#[kani::proof]
+#[kani::unwind(257)]
+fn decrease_key_keeps_min() {
+    let (mut feap, mut vals) = any_heap();
+    kani::assume(vals.len() > 1);
+    vals.sort_unstable();
+    assert_eq!(feap.extract_min(), Some(vals.remove(0)));
+
+    let idx: usize = kani::any();
+    kani::assume(idx < vals.len());
+    let new: i8 = kani::any();
+    kani::assume(new <= vals[idx]);
+    feap.decrease_key(&vals[idx], new);
+    vals[idx] = new;
+
+    feap.validate();
+    assert_eq!(feap.get_min(), vals.iter().min());
+}
+
+/// Clearing frees every node and leaves an empty, reusable heap.
+#[kani::proof]
+#[kani::unwind(257)]
+fn clear_empties() {
+    let (mut feap, _) = any_heap();
+    feap.clear();
+    assert_eq!(feap.get_min(), None);
+    assert_eq!(feap.root_list.len(), MAX_DEGREE);
+    feap.insert(1);
+    assert_eq!(feap.extract_min(), Some(1));
+}
@@ -0,0 +1,187 @@
+//! The minimum over a sliding time window of a stream.
+
+use core::cmp::Ordering;
+use std::collections::VecDeque;
+
+use crate::FibHeap;
+
+/// The number of expired heap entries which is always tolerated before the
+/// heap is rebuilt.
+const COMPACTION_SLACK: usize = 32;
+
+/// A value in the heap together with the id of its push. Entries with an id
+/// below the expiry watermark are tombstones. Equal values are ordered by
+/// their id, which keeps the order consistent with the equality.
+struct Entry<T> {
+    val: T,
+    id: u64,
+}
+
+impl<T: PartialOrd> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.val == other.val
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.val.partial_cmp(&other.val)? {
+            Ordering::Equal => Some(self.id.cmp(&other.id)),
+            ord => Some(ord),
+        }
+    }
+}
+
+/// The minimum of all values pushed within a sliding time window.
+///
+/// Values which fall out of the window are not searched for in the heap.
+/// Instead everything pushed before the oldest value still in the window is
+/// considered a tombstone, which is dropped once it comes up as the minimum.
+/// When tombstones start to outnumber the live values, the heap is rebuilt.
+///
+/// The window only moves forward: timestamps have to be pushed in
+/// non-decreasing order, and a value which fell out of the window once stays
+/// out, even if a later query uses a wider window.
+///
+/// ```rust
+/// use feap::WindowedMin;
+///
+/// let mut window = WindowedMin::new();
+/// window.push(5, 0);
+/// window.push(8, 10);
+/// window.push(7, 20);
+///
+/// assert_eq!(window.min_in_window(20, 30), Some(&5));
+/// assert_eq!(window.min_in_window(25, 15), Some(&7));
+/// ```
+pub struct WindowedMin<T: PartialOrd> {
+    /// Live values and tombstones, smallest first.
+    heap: FibHeap<Entry<T>>,
+
+    /// The timestamp, id and value of every value still in the window, in
+    /// push order.
+    live: VecDeque<(u64, u64, T)>,
+
+    /// The id the next pushed value gets.
+    next_id: u64,
+}
+
+impl<T: PartialOrd + Clone> Default for WindowedMin<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone> WindowedMin<T> {
+    /// Create a new, empty [`WindowedMin`].
+    pub fn new() -> Self {
+        Self {
+            heap: FibHeap::new(),
+            live: VecDeque::new(),
+            next_id: 0,
+        }
+    }
+
+    /// `push` adds `val` seen at `timestamp`. A timestamp older than the last
+    /// pushed one is treated as equal to it.
+    pub fn push(&mut self, val: T, timestamp: u64) {
+        let timestamp = self.live.back().map_or(timestamp, |&(t, _, _)| t.max(timestamp));
+        let id = self.next_id;
+        self.next_id += 1;
+        self.live.push_back((timestamp, id, val.clone()));
+        self.heap.insert(Entry { val, id });
+    }
+
+    /// `min_in_window` returns the smallest value pushed at or after
+    /// `now - width`. Everything older is dropped from the window for good.
+    pub fn min_in_window(&mut self, now: u64, width: u64) -> Option<&T> {
+        let cutoff = now.saturating_sub(width);
+        while self.live.front().is_some_and(|&(t, _, _)| t < cutoff) {
+            self.live.pop_front();
+        }
+
+        if self.heap.len() > 2 * self.live.len() + COMPACTION_SLACK {
+            self.rebuild();
+        }
+
+        let watermark = self.live.front().map_or(self.next_id, |&(_, id, _)| id);
+        while self.heap.get_min().is_some_and(|e| e.id < watermark) {
+            self.heap.extract_min();
+        }
+        self.heap.get_min().map(|e| &e.val)
+    }
+
+    /// Rebuilds the heap from the values still in the window.
+    fn rebuild(&mut self) {
+        self.heap.clear();
+        for (_, id, val) in &self.live {
+            self.heap.insert(Entry { val: val.clone(), id: *id });
+        }
+    }
+
+    /// `len` returns the number of values which were in the window at the
+    /// last query, plus the ones pushed since.
+    pub fn len(&self) -> usize {
+        self.live.len()
+    }
+
+    /// `is_empty` returns whether [`len`](WindowedMin::len) is zero.
+    pub fn is_empty(&self) -> bool {
+        self.live.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cmp::Ordering;
+
+    use super::{Entry, WindowedMin};
+
+    #[test]
+    fn matches_scan() {
+        let mut window = WindowedMin::new();
+        let mut pushed = Vec::new();
+        let mut state = 5u32;
+        // The scan over all values is too slow under Miri for many of them.
+        let n = if cfg!(miri) { 300 } else { 2000 };
+        for now in 0..n {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            // Mostly decreasing values, so expired ones pile up below the top.
+            let val = 100_000 - now as i64 * 40 + i64::from(state >> 24);
+            window.push(val, now);
+            pushed.push((now, val));
+
+            let expected = pushed.iter()
+                .filter(|&&(t, _)| t + 50 >= now)
+                .map(|&(_, v)| v)
+                .min();
+            assert_eq!(window.min_in_window(now, 50).copied(), expected);
+            assert!(window.heap.len() <= 2 * window.len() + super::COMPACTION_SLACK + 1);
+        }
+    }
+
+    #[test]
+    fn equal_values_keep_push_order() {
+        let a = Entry { val: 3, id: 0 };
+        let b = Entry { val: 3, id: 1 };
+        assert!(a != b);
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Less));
+
+        let mut window = WindowedMin::new();
+        window.push(3, 0);
+        window.push(3, 10);
+        assert_eq!(window.min_in_window(15, 8), Some(&3));
+        assert_eq!(window.heap.len(), 1);
+    }
+
+    #[test]
+    fn empty_window() {
+        let mut window = WindowedMin::new();
+        assert_eq!(window.min_in_window(10, 5), None);
+        window.push('a', 1);
+        window.push('b', 0);
+        assert_eq!(window.min_in_window(5, 4), Some(&'a'));
+        assert_eq!(window.min_in_window(6, 4), None);
+        assert!(window.is_empty());
+    }
+}
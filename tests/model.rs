@@ -0,0 +1,98 @@
+//! Property based tests which run arbitrary operation sequences against a
+//! [`FibHeap`] and a simple sorted [`Vec`] model and check that both agree.
+
+use feap::FibHeap;
+use proptest::prelude::*;
+
+/// A single operation applied to both the heap and the model.
+#[derive(Debug, Clone)]
+enum Op {
+    Insert(i32),
+    ExtractMin,
+    /// Decrease the element at `idx % len` of the model by `delta`.
+    DecreaseKey { idx: usize, delta: u16 },
+    Clear,
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        6 => (-1000..1000i32).prop_map(Op::Insert),
+        3 => Just(Op::ExtractMin),
+        2 => (any::<usize>(), any::<u16>())
+            .prop_map(|(idx, delta)| Op::DecreaseKey { idx, delta }),
+        1 => Just(Op::Clear),
+    ]
+}
+
+/// The reference model, a [`Vec`] which is kept sorted in ascending order.
+#[derive(Default)]
+struct Model(Vec<i32>);
+
+impl Model {
+    fn insert(&mut self, val: i32) {
+        let idx = self.0.partition_point(|&v| v < val);
+        self.0.insert(idx, val);
+    }
+
+    fn extract_min(&mut self) -> Option<i32> {
+        if self.0.is_empty() { None } else { Some(self.0.remove(0)) }
+    }
+}
+
+fn apply(feap: &mut FibHeap<i32>, model: &mut Model, op: &Op) {
+    match *op {
+        Op::Insert(v) => {
+            feap.insert(v);
+            model.insert(v);
+        }
+        Op::ExtractMin => {
+            assert_eq!(feap.extract_min(), model.extract_min());
+        }
+        Op::DecreaseKey { idx, delta } => {
+            if model.0.is_empty() {
+                return;
+            }
+            let old = model.0.remove(idx % model.0.len());
+            let new = old - i32::from(delta);
+            feap.decrease_key(&old, new);
+            model.insert(new);
+        }
+        Op::Clear => {
+            feap.clear();
+            model.0.clear();
+        }
+    }
+}
+
+proptest! {
+    // Keep the number of cases small under Miri, which is orders of magnitude
+    // slower than a native run. Its isolation from the host also rules out
+    // the files failing cases are saved to.
+    #![proptest_config(ProptestConfig {
+        cases: if cfg!(miri) { 8 } else { ProptestConfig::default().cases },
+        failure_persistence: if cfg!(miri) {
+            None
+        } else {
+            ProptestConfig::default().failure_persistence
+        },
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn matches_sorted_vec(ops in prop::collection::vec(op(), 0..400)) {
+        let mut feap = FibHeap::new();
+        let mut model = Model::default();
+
+        for op in &ops {
+            apply(&mut feap, &mut model, op);
+            prop_assert_eq!(feap.get_min(), model.0.first());
+            prop_assert_eq!(feap.len(), model.0.len());
+        }
+
+        let mut drained = Vec::new();
+        while let Some(v) = feap.extract_min() {
+            drained.push(v);
+        }
+        prop_assert_eq!(drained, model.0);
+    }
+}